@@ -0,0 +1,635 @@
+mod account;
+mod admin;
+mod archive;
+mod assignments;
+mod dependencies;
+mod projects;
+mod recurring;
+mod sharing;
+mod subtasks;
+mod tags;
+mod trash;
+mod workspaces;
+
+use std::collections::{BTreeMap, HashMap};
+
+use chrono::{Datelike, Days, Local, NaiveDate, TimeDelta, Utc};
+
+use crate::auth::{hash_password, verify_password, PasswordCheck, DUMMY_HASH};
+use crate::error::{Result, TodoError};
+use crate::model::{Due, Permission, Priority, Project, Role, Status, Task, User, Workspace};
+use crate::recurrence::Recurrence;
+use crate::storage::{JsonStorage, Storage};
+use crate::workflow::Workflow;
+
+/// Optional settings for [`TodoApp::create_task`].
+#[derive(Debug, Clone, Default)]
+pub struct TaskOptions {
+    pub tags: Vec<String>,
+    /// A project the current user can add tasks to: their own, or one shared
+    /// with them or in their workspace with at least editor access.
+    pub project: Option<String>,
+    /// One of the current user's tasks to nest the new task under.
+    pub parent: Option<u32>,
+    pub recurrence: Option<Recurrence>,
+    /// A workspace the current user is at least a member of.
+    pub workspace: Option<String>,
+}
+
+/// The todo engine: users, their tasks and the current session.
+///
+/// Every mutating call persists the affected data immediately.
+pub struct TodoApp {
+    tasks: HashMap<u32, Task>,
+    trash: HashMap<u32, Task>,
+    archive: HashMap<u32, Task>,
+    users: HashMap<String, User>,
+    projects: Vec<Project>,
+    workspaces: Vec<Workspace>,
+    current_user: Option<String>,
+    next_task_id: u32,
+    storage: Box<dyn Storage>,
+    workflow: Workflow,
+    trash_retention: Option<TimeDelta>,
+    auto_archive: Option<TimeDelta>,
+}
+
+impl Default for TodoApp {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl TodoApp {
+    /// Creates an empty app backed by `tasks.json` and `users.json` in the
+    /// working directory.
+    pub fn new() -> Self {
+        Self::with_storage(Box::new(JsonStorage::default()))
+    }
+
+    /// Creates an empty app backed by `storage`. Call [`load_tasks`](Self::load_tasks)
+    /// and [`load_users`](Self::load_users) to read existing data.
+    pub fn with_storage(storage: Box<dyn Storage>) -> Self {
+        Self {
+            tasks: HashMap::new(),
+            trash: HashMap::new(),
+            archive: HashMap::new(),
+            users: HashMap::new(),
+            projects: Vec::new(),
+            workspaces: Vec::new(),
+            current_user: None,
+            next_task_id: 1,
+            storage,
+            workflow: Workflow::default(),
+            trash_retention: Some(TimeDelta::days(30)),
+            auto_archive: None,
+        }
+    }
+
+    /// Registers a new user. Does not log them in. The very first user
+    /// becomes an admin.
+    pub fn register(&mut self, username: String, password: String) -> Result<()> {
+        if username.is_empty() {
+            return Err(TodoError::Validation("Username must not be empty"));
+        }
+        if password.is_empty() {
+            return Err(TodoError::Validation("Password must not be empty"));
+        }
+
+        let password = hash_password(&password)?;
+        self.update_users(|users| {
+            if users.contains_key(&username) {
+                return Err(TodoError::DuplicateUser);
+            }
+            let admin = users.is_empty();
+            users.insert(username.clone(), User {
+                username,
+                password,
+                admin,
+                locked: false,
+                registered_at: Some(Utc::now()),
+            });
+            Ok(())
+        })
+    }
+
+    /// Logs in as `username`, upgrading a legacy plaintext password on success.
+    pub fn login(&mut self, username: String, password: String) -> Result<()> {
+        self.load_users()?;
+        let Some(user) = self.users.get(&username) else {
+            // Do the same work as for a wrong password, so response times
+            // don't reveal which accounts exist.
+            verify_password(&password, DUMMY_HASH);
+            return Err(TodoError::InvalidCredentials);
+        };
+
+        match verify_password(&password, &user.password) {
+            PasswordCheck::Valid => {}
+            PasswordCheck::ValidLegacy => {
+                let hash = hash_password(&password)?;
+                self.update_users(|users| {
+                    if let Some(user) = users.get_mut(&username) {
+                        user.password = hash;
+                    }
+                    Ok(())
+                })?;
+            }
+            PasswordCheck::Invalid => return Err(TodoError::InvalidCredentials),
+        }
+        // Checked after the password so locking doesn't reveal which accounts exist.
+        if self.users.get(&username).is_some_and(|user| user.locked) {
+            return Err(TodoError::AccountLocked);
+        }
+
+        self.current_user = Some(username);
+        Ok(())
+    }
+
+    /// Returns the username of the logged-in user, if any.
+    pub fn current_user(&self) -> Option<&str> {
+        self.current_user.as_deref()
+    }
+
+    /// Ends the current session.
+    pub fn logout(&mut self) {
+        self.current_user = None;
+    }
+
+    /// Adds a task owned by the current user and returns its ID.
+    pub fn add_task(&mut self, title: String, description: String, priority: Priority, due: Option<Due>) -> Result<u32> {
+        self.create_task(title, description, priority, due, TaskOptions::default())
+    }
+
+    /// Like [`add_task`](Self::add_task), also setting `options`. Every
+    /// option is checked before anything is saved, so on error no task is
+    /// created.
+    pub fn create_task(
+        &mut self,
+        title: String,
+        description: String,
+        priority: Priority,
+        due: Option<Due>,
+        options: TaskOptions,
+    ) -> Result<u32> {
+        let user_id = self.current_user.clone().ok_or(TodoError::NotLoggedIn)?;
+        if title.is_empty() {
+            return Err(TodoError::Validation("Title must not be empty"));
+        }
+        let tags = tags::normalize_tags(options.tags.iter().map(String::as_str))?;
+        if let Some(recurrence) = &options.recurrence {
+            recurrence.validate().map_err(TodoError::Validation)?;
+        }
+
+        self.update_tasks(|app| {
+            let project = options.project.as_ref()
+                .map(|name| app.find_project(&user_id, name, Permission::Editor).cloned())
+                .transpose()?;
+            if let Some(parent_id) = options.parent {
+                app.check_task(parent_id, &user_id, Permission::Owner, None)?;
+            }
+            if let Some(workspace) = &options.workspace {
+                app.check_role(workspace, &user_id, Role::Member)?;
+            }
+
+            let mut task = Task {
+                id: app.next_task_id,
+                title,
+                description,
+                status: Status::Todo,
+                legacy_completed: None,
+                priority,
+                created_at: Utc::now(),
+                user_id,
+                version: 0,
+                due_at: None,
+                all_day: false,
+                tags,
+                project: None,
+                project_owner: None,
+                parent_id: options.parent,
+                blocked_by: Default::default(),
+                recurrence: options.recurrence,
+                started_at: None,
+                completed_at: None,
+                cancelled_at: None,
+                deleted_at: None,
+                archived_at: None,
+                shared_with: Default::default(),
+                assignee: None,
+                assignments: Vec::new(),
+                workspace: options.workspace,
+            };
+            task.set_due(due);
+            task.set_project(project.as_ref());
+
+            let id = app.next_task_id;
+            app.tasks.insert(id, task);
+            app.next_task_id += 1;
+            Ok(id)
+        })
+    }
+
+    /// Replaces the rules for [`set_status`](Self::set_status).
+    pub fn set_workflow(&mut self, workflow: Workflow) {
+        self.workflow = workflow;
+    }
+
+    /// Moves a task the current user can edit to `status`, if the workflow
+    /// allows it, and stamps the matching `*_at` field.
+    ///
+    /// A task can't be done while any of its subtasks or blockers are still
+    /// open. Completing a recurring task keeps it as the record of this
+    /// occurrence and creates the next one, whose ID is returned; the repeat
+    /// rule moves to the new task.
+    pub fn set_status(&mut self, task_id: u32, status: Status) -> Result<Option<u32>> {
+        let user_id = self.current_user.clone().ok_or(TodoError::NotLoggedIn)?;
+        let seen = self.tasks.get(&task_id).map(|task| task.version);
+
+        self.update_tasks(|app| {
+            app.check_task(task_id, &user_id, Permission::Editor, seen)?;
+            let current = app.tasks[&task_id].status;
+            if !app.workflow.allows(current, status) {
+                return Err(TodoError::InvalidTransition(current, status));
+            }
+            if status == Status::Done {
+                if app.child_ids(task_id).iter().any(|id| app.tasks[id].is_open()) {
+                    return Err(TodoError::OpenSubtasks);
+                }
+                if app.is_blocked(&app.tasks[&task_id]) {
+                    return Err(TodoError::Blocked);
+                }
+            }
+
+            let now = Utc::now();
+            let task = app.tasks.get_mut(&task_id).ok_or(TodoError::TaskNotFound)?;
+            task.status = status;
+            task.version += 1;
+            match status {
+                Status::InProgress => {
+                    task.started_at.get_or_insert(now);
+                }
+                Status::Done => task.completed_at = Some(now),
+                Status::Cancelled => task.cancelled_at = Some(now),
+                Status::Todo | Status::Blocked => {
+                    task.completed_at = None;
+                    task.cancelled_at = None;
+                }
+            }
+
+            if status == Status::Done {
+                app.spawn_next_occurrence(task_id)
+            } else {
+                Ok(None)
+            }
+        })
+    }
+
+    /// Marks a task the current user can edit as done; see [`set_status`](Self::set_status).
+    pub fn complete_task(&mut self, task_id: u32) -> Result<Option<u32>> {
+        self.set_status(task_id, Status::Done)
+    }
+
+    /// Moves a done or cancelled task back to `todo`.
+    pub fn reopen_task(&mut self, task_id: u32) -> Result<()> {
+        self.set_status(task_id, Status::Todo).map(|_| ())
+    }
+
+    /// Replaces the title, description, priority and due date of a task the
+    /// current user can edit.
+    pub fn edit_task(
+        &mut self,
+        task_id: u32,
+        title: String,
+        description: String,
+        priority: Priority,
+        due: Option<Due>,
+    ) -> Result<()> {
+        if title.is_empty() {
+            return Err(TodoError::Validation("Title must not be empty"));
+        }
+
+        self.modify_task(task_id, |task| {
+            task.title = title;
+            task.description = description;
+            task.priority = priority;
+            task.set_due(due);
+        })
+    }
+
+    /// Moves a task the current user can edit, together with all of its
+    /// subtasks, which they must be able to edit too, to the trash. See [`restore_task`](Self::restore_task) and
+    /// [`purge_trash`](Self::purge_trash).
+    pub fn delete_task(&mut self, task_id: u32) -> Result<()> {
+        let user_id = self.current_user.clone().ok_or(TodoError::NotLoggedIn)?;
+        let seen = self.tasks.get(&task_id).map(|task| task.version);
+
+        self.update_tasks(|app| {
+            app.check_task(task_id, &user_id, Permission::Editor, seen)?;
+            for id in app.descendant_ids(task_id) {
+                app.check_task(id, &user_id, Permission::Editor, None)?;
+            }
+            app.trash_task(task_id);
+            Ok(())
+        })
+    }
+
+    /// Returns a task the current user owns or that was shared with them.
+    pub fn get_task(&self, task_id: u32) -> Result<&Task> {
+        let user_id = self.current_user.as_ref().ok_or(TodoError::NotLoggedIn)?;
+
+        let task = self.tasks.get(&task_id).ok_or(TodoError::TaskNotFound)?;
+        if self.permission(task, user_id).is_none() {
+            return Err(TodoError::Unauthorized);
+        }
+        Ok(task)
+    }
+
+    /// Returns the current user's tasks and those shared with them, highest
+    /// priority first and oldest first within a priority. Shared tasks keep
+    /// their owner's `user_id`.
+    pub fn list_tasks(&self) -> Result<Vec<&Task>> {
+        let user_id = self.current_user.as_ref().ok_or(TodoError::NotLoggedIn)?;
+
+        let mut tasks: Vec<&Task> = self.tasks.values()
+            .filter(|task| self.permission(task, user_id).is_some())
+            .collect();
+        tasks.sort_by(|a, b| b.priority.cmp(&a.priority).then(a.id.cmp(&b.id)));
+        Ok(tasks)
+    }
+
+    /// Returns the current user's open tasks whose deadline has passed.
+    pub fn overdue_tasks(&self) -> Result<Vec<&Task>> {
+        let now = Local::now();
+        Ok(self.list_tasks()?
+            .into_iter()
+            .filter(|task| task.is_overdue(now))
+            .collect())
+    }
+
+    /// Returns the current user's open tasks due today, including ones whose
+    /// time today has already passed.
+    pub fn tasks_due_today(&self) -> Result<Vec<&Task>> {
+        let today = Local::now().date_naive();
+        self.open_tasks_due_between(today, today)
+    }
+
+    /// Returns the current user's open tasks due from today through Sunday.
+    pub fn tasks_due_this_week(&self) -> Result<Vec<&Task>> {
+        let today = Local::now().date_naive();
+        let sunday = today + Days::new(6 - u64::from(today.weekday().num_days_from_monday()));
+        self.open_tasks_due_between(today, sunday)
+    }
+
+    /// Groups the current user's open tasks that have a due date by day,
+    /// earliest first. Overdue tasks appear under their original day.
+    pub fn agenda(&self) -> Result<BTreeMap<NaiveDate, Vec<&Task>>> {
+        let mut days: BTreeMap<NaiveDate, Vec<&Task>> = BTreeMap::new();
+        for task in self.list_tasks()? {
+            if let (true, Some(due)) = (task.is_open(), task.due()) {
+                days.entry(due.date()).or_default().push(task);
+            }
+        }
+        Ok(days)
+    }
+
+    fn open_tasks_due_between(&self, first: NaiveDate, last: NaiveDate) -> Result<Vec<&Task>> {
+        Ok(self.list_tasks()?
+            .into_iter()
+            .filter(|task| task.is_open())
+            .filter(|task| task.due().is_some_and(|due| (first..=last).contains(&due.date())))
+            .collect())
+    }
+
+    /// Applies `f` to a task the current user can edit and bumps its version.
+    fn modify_task(&mut self, task_id: u32, f: impl FnOnce(&mut Task)) -> Result<()> {
+        let user_id = self.current_user.clone().ok_or(TodoError::NotLoggedIn)?;
+        let seen = self.tasks.get(&task_id).map(|task| task.version);
+
+        self.update_tasks(|app| {
+            app.check_task(task_id, &user_id, Permission::Editor, seen)?;
+            let task = app.tasks.get_mut(&task_id).ok_or(TodoError::TaskNotFound)?;
+            f(task);
+            task.version += 1;
+            Ok(())
+        })
+    }
+
+    /// Checks that `task_id` exists, that `user_id` has at least `needed`
+    /// access to it, and that it still has the version this session last saw
+    /// (`None` if it had never seen the task).
+    fn check_task(&self, task_id: u32, user_id: &str, needed: Permission, seen: Option<u32>) -> Result<()> {
+        let task = self.tasks.get(&task_id).ok_or(TodoError::TaskNotFound)?;
+        if self.permission(task, user_id).is_none_or(|permission| permission < needed) {
+            return Err(TodoError::Unauthorized);
+        }
+        if seen.is_some_and(|version| version != task.version) {
+            return Err(TodoError::Conflict);
+        }
+        Ok(())
+    }
+
+    /// Runs `f` against freshly loaded tasks while holding the storage lock,
+    /// then saves. This keeps concurrent sessions from overwriting each
+    /// other's changes or handing out the same task ID twice. Expired trash
+    /// is purged and stale closed tasks are archived on the way.
+    fn update_tasks<T>(&mut self, f: impl FnOnce(&mut Self) -> Result<T>) -> Result<T> {
+        let _lock = self.storage.lock()?;
+        self.load_tasks()?;
+        let result = f(self)?;
+        self.purge_expired_trash();
+        self.auto_archive();
+        self.save_tasks()?;
+        Ok(result)
+    }
+
+    /// Like [`update_tasks`](Self::update_tasks), for the user table.
+    fn update_users<T>(&mut self, f: impl FnOnce(&mut HashMap<String, User>) -> Result<T>) -> Result<T> {
+        let _lock = self.storage.lock()?;
+        self.load_users()?;
+        let result = f(&mut self.users)?;
+        self.save_users()?;
+        Ok(result)
+    }
+
+    /// Persists all tasks, the trash, the archive, projects, workspaces and
+    /// the task ID counter to the storage backend.
+    pub fn save_tasks(&mut self) -> Result<()> {
+        self.storage.begin()?;
+        match self.write_tasks() {
+            Ok(()) => self.storage.commit(),
+            Err(err) => {
+                // The write error is the one worth reporting.
+                let _ = self.storage.rollback();
+                Err(err)
+            }
+        }
+    }
+
+    fn write_tasks(&mut self) -> Result<()> {
+        // The counter goes first: if we crash in between, it is merely ahead.
+        self.storage.save_next_task_id(self.next_task_id)?;
+        self.storage.save_workspaces(&self.workspaces)?;
+        self.storage.save_projects(&self.projects)?;
+
+        // Tasks move between the task list and the trash or archive both
+        // ways. Saving the trash and archive with the tasks leaving them still
+        // in, then the tasks, then the final trash and archive means a save
+        // cut short leaves a task in two places, which `load_tasks` resolves,
+        // but never in neither.
+        let trash = with_returning(self.storage.load_trash()?, &self.trash, &self.tasks);
+        let archive = with_returning(self.storage.load_archive()?, &self.archive, &self.tasks);
+        self.storage.save_trash(trash.as_ref().unwrap_or(&self.trash))?;
+        self.storage.save_archive(archive.as_ref().unwrap_or(&self.archive))?;
+        self.storage.save_tasks(&self.tasks)?;
+        if trash.is_some() {
+            self.storage.save_trash(&self.trash)?;
+        }
+        if archive.is_some() {
+            self.storage.save_archive(&self.archive)?;
+        }
+        Ok(())
+    }
+
+    /// Reads tasks, the trash, the archive, projects and workspaces from the
+    /// storage backend, replacing those in memory.
+    ///
+    /// Task IDs are never reused: the next ID comes from the persisted counter,
+    /// or from the highest existing ID for data written before it existed.
+    pub fn load_tasks(&mut self) -> Result<()> {
+        self.tasks = self.storage.load_tasks()?;
+        for task in self.tasks.values_mut() {
+            task.migrate_legacy();
+        }
+        self.trash = self.storage.load_trash()?;
+        self.archive = self.storage.load_archive()?;
+        // A task moved by a crashed save may briefly be in both places.
+        self.trash.retain(|id, _| !self.tasks.contains_key(id));
+        self.archive.retain(|id, _| !self.tasks.contains_key(id));
+        self.projects = self.storage.load_projects()?;
+        self.workspaces = self.storage.load_workspaces()?;
+        let after_max = self.tasks.keys().chain(self.trash.keys()).chain(self.archive.keys()).max().map_or(1, |max| max + 1);
+        self.next_task_id = self.storage.load_next_task_id()?.map_or(after_max, |id| id.max(after_max));
+        Ok(())
+    }
+
+    /// Persists all users to the storage backend.
+    pub fn save_users(&mut self) -> Result<()> {
+        self.storage.save_users(&self.users)
+    }
+
+    /// Reads users from the storage backend, replacing those in memory.
+    ///
+    /// Data written before users had an admin role has no admin; the oldest
+    /// account is treated as one and saved that way on the next write.
+    pub fn load_users(&mut self) -> Result<()> {
+        self.users = self.storage.load_users()?;
+        if !self.users.values().any(|user| user.admin) {
+            // `None` (registered before the timestamp existed) sorts first.
+            let oldest = self.users.values_mut().min_by(|a, b| {
+                a.registered_at.cmp(&b.registered_at).then_with(|| a.username.cmp(&b.username))
+            });
+            if let Some(user) = oldest {
+                user.admin = true;
+            }
+        }
+        Ok(())
+    }
+}
+
+/// `current` plus the tasks in `stored` that are moving back to `tasks`, or
+/// `None` if there are none.
+fn with_returning(
+    stored: HashMap<u32, Task>,
+    current: &HashMap<u32, Task>,
+    tasks: &HashMap<u32, Task>,
+) -> Option<HashMap<u32, Task>> {
+    let returning: Vec<(u32, Task)> = stored.into_iter()
+        .filter(|(id, _)| tasks.contains_key(id) && !current.contains_key(id))
+        .collect();
+    if returning.is_empty() {
+        return None;
+    }
+    let mut union = current.clone();
+    union.extend(returning);
+    Some(union)
+}
+
+#[cfg(test)]
+pub(crate) mod tests {
+    use std::path::{Path, PathBuf};
+
+    use super::*;
+
+    /// A fresh, empty directory for one test's files.
+    pub(crate) fn temp_dir(name: &str) -> PathBuf {
+        let dir = std::env::temp_dir().join(format!("todo-test-{}-{}", std::process::id(), name));
+        let _ = std::fs::remove_dir_all(&dir);
+        std::fs::create_dir_all(&dir).unwrap();
+        dir
+    }
+
+    /// A session on the JSON files in `dir`, logged in as alice, who is
+    /// registered on first use.
+    pub(crate) fn json_app(dir: &Path) -> TodoApp {
+        let storage = JsonStorage::new(dir.join("tasks.json"), dir.join("users.json"));
+        let mut app = TodoApp::with_storage(Box::new(storage));
+        match app.register("alice".into(), "secret".into()) {
+            Ok(()) | Err(TodoError::DuplicateUser) => {}
+            Err(err) => panic!("{}", err),
+        }
+        app.login("alice".into(), "secret".into()).unwrap();
+        app.load_tasks().unwrap();
+        app
+    }
+
+    fn add(app: &mut TodoApp, title: &str) -> u32 {
+        app.add_task(title.into(), String::new(), Priority::Normal, None).unwrap()
+    }
+
+    #[test]
+    fn editing_a_task_changed_by_another_session_conflicts() {
+        let dir = temp_dir("conflict");
+        let mut first = json_app(&dir);
+        let id = add(&mut first, "task");
+        let mut second = json_app(&dir);
+
+        first.edit_task(id, "first".into(), String::new(), Priority::Normal, None).unwrap();
+        let stale = second.edit_task(id, "second".into(), String::new(), Priority::Normal, None);
+        assert!(matches!(stale, Err(TodoError::Conflict)));
+
+        // The failed attempt reloaded, so a retry goes through.
+        second.edit_task(id, "second".into(), String::new(), Priority::Normal, None).unwrap();
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn sessions_build_on_each_others_saves() {
+        let dir = temp_dir("reload");
+        let mut first = json_app(&dir);
+        let mut second = json_app(&dir);
+
+        let a = add(&mut first, "from first");
+        let b = add(&mut second, "from second");
+        assert_ne!(a, b);
+        let c = add(&mut first, "first again");
+        assert!(c > b);
+
+        let third = json_app(&dir);
+        assert_eq!(third.list_tasks().unwrap().len(), 3);
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn ids_of_purged_tasks_are_not_reused() {
+        let dir = temp_dir("no-reuse");
+        let mut app = json_app(&dir);
+        add(&mut app, "kept");
+        let last = add(&mut app, "purged");
+        app.delete_task(last).unwrap();
+        app.purge_trash(Some(last)).unwrap();
+
+        let mut app = json_app(&dir);
+        assert_eq!(add(&mut app, "new"), last + 1);
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+}
@@ -0,0 +1,79 @@
+use super::TodoApp;
+use crate::auth::{hash_password, verify_password, PasswordCheck};
+use crate::error::{Result, TodoError};
+use crate::model::Task;
+
+impl TodoApp {
+    /// Changes the current user's password after re-checking the old one.
+    pub fn change_password(&mut self, old: &str, new: &str) -> Result<()> {
+        let user_id = self.current_user.clone().ok_or(TodoError::NotLoggedIn)?;
+        if new.is_empty() {
+            return Err(TodoError::Validation("Password must not be empty"));
+        }
+        self.verify_current_password(&user_id, old)?;
+
+        let password = hash_password(new)?;
+        self.update_users(|users| {
+            let user = users.get_mut(&user_id).ok_or(TodoError::UserNotFound)?;
+            user.password = password;
+            Ok(())
+        })
+    }
+
+    /// Returns every task the current user created, including archived and
+    /// trashed ones, by ID. These are what
+    /// [`delete_account`](Self::delete_account) deletes.
+    pub fn account_tasks(&self) -> Result<Vec<&Task>> {
+        let user_id = self.current_user.as_ref().ok_or(TodoError::NotLoggedIn)?;
+
+        let mut tasks: Vec<&Task> = self.tasks.values()
+            .chain(self.archive.values())
+            .chain(self.trash.values())
+            .filter(|task| task.user_id == *user_id)
+            .collect();
+        tasks.sort_by_key(|task| task.id);
+        Ok(tasks)
+    }
+
+    /// Removes the current user's account after re-checking their password,
+    /// and logs them out. Their tasks, including archived and trashed ones,
+    /// and projects are deleted; the tasks are returned. To keep a copy,
+    /// export [`account_tasks`](Self::account_tasks) before calling this.
+    pub fn delete_account(&mut self, password: &str) -> Result<Vec<Task>> {
+        let user_id = self.current_user.clone().ok_or(TodoError::NotLoggedIn)?;
+        self.verify_current_password(&user_id, password)?;
+
+        let removed = self.update_tasks(|app| {
+            app.load_users()?;
+            let is_admin = app.users.get(&user_id).is_some_and(|user| user.admin);
+            let other_admin = app.users.values().any(|user| user.admin && user.username != user_id);
+            if is_admin && !other_admin && app.users.len() > 1 {
+                return Err(TodoError::Validation("You are the only admin; promote another user first"));
+            }
+
+            let mut removed: Vec<Task> = app.tasks.values()
+                .chain(app.archive.values())
+                .chain(app.trash.values())
+                .filter(|task| task.user_id == user_id)
+                .cloned()
+                .collect();
+            removed.sort_by_key(|task| task.id);
+
+            app.remove_user(&user_id, None)?;
+            app.save_users()?;
+            Ok(removed)
+        })?;
+
+        self.logout();
+        Ok(removed)
+    }
+
+    fn verify_current_password(&mut self, user_id: &str, password: &str) -> Result<()> {
+        self.load_users()?;
+        let user = self.users.get(user_id).ok_or(TodoError::UserNotFound)?;
+        match verify_password(password, &user.password) {
+            PasswordCheck::Valid | PasswordCheck::ValidLegacy => Ok(()),
+            PasswordCheck::Invalid => Err(TodoError::InvalidCredentials),
+        }
+    }
+}
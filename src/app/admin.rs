@@ -0,0 +1,178 @@
+use super::TodoApp;
+use crate::auth::hash_password;
+use crate::error::{Result, TodoError};
+use crate::model::{Role, User};
+
+impl TodoApp {
+    /// Returns every registered user sorted by name. Admin only.
+    pub fn list_users(&mut self) -> Result<Vec<&User>> {
+        self.require_admin()?;
+
+        let mut users: Vec<&User> = self.users.values().collect();
+        users.sort_by(|a, b| a.username.cmp(&b.username));
+        Ok(users)
+    }
+
+    /// Sets a new password for `username`, e.g. after they forgot theirs.
+    /// Admin only.
+    pub fn reset_password(&mut self, username: &str, password: &str) -> Result<()> {
+        self.require_admin()?;
+        if password.is_empty() {
+            return Err(TodoError::Validation("Password must not be empty"));
+        }
+
+        let password = hash_password(password)?;
+        self.update_users(|users| {
+            let user = users.get_mut(username).ok_or(TodoError::UserNotFound)?;
+            user.password = password;
+            Ok(())
+        })
+    }
+
+    /// Locks or unlocks `username`. A locked account can't log in. Admin
+    /// only; admins can't lock themselves out.
+    pub fn set_locked(&mut self, username: &str, locked: bool) -> Result<()> {
+        let admin = self.require_admin()?;
+        if locked && username == admin {
+            return Err(TodoError::Validation("You can't lock your own account"));
+        }
+
+        self.update_users(|users| {
+            let user = users.get_mut(username).ok_or(TodoError::UserNotFound)?;
+            user.locked = locked;
+            Ok(())
+        })
+    }
+
+    /// Grants or revokes the admin role. Admin only; the last admin can't be
+    /// demoted.
+    pub fn set_admin(&mut self, username: &str, admin: bool) -> Result<()> {
+        self.require_admin()?;
+
+        self.update_users(|users| {
+            let user = users.get_mut(username).ok_or(TodoError::UserNotFound)?;
+            user.admin = admin;
+            if !users.values().any(|user| user.admin) {
+                return Err(TodoError::Validation("There must be at least one admin"));
+            }
+            Ok(())
+        })
+    }
+
+    /// Removes another user's account. Their tasks, trash, archive and
+    /// projects go to `reassign_to` if given, and are deleted otherwise.
+    /// Admin only.
+    pub fn delete_user(&mut self, username: &str, reassign_to: Option<&str>) -> Result<()> {
+        let admin = self.require_admin()?;
+        if username == admin {
+            return Err(TodoError::Validation("Use delete-account to remove your own account"));
+        }
+        if reassign_to == Some(username) {
+            return Err(TodoError::Validation("Can't reassign tasks to the user being deleted"));
+        }
+
+        self.update_tasks(|app| {
+            app.load_users()?;
+            if !app.users.contains_key(username) {
+                return Err(TodoError::UserNotFound);
+            }
+            if reassign_to.is_some_and(|target| !app.users.contains_key(target)) {
+                return Err(TodoError::UserNotFound);
+            }
+            app.remove_user(username, reassign_to)?;
+            app.save_users()
+        })
+    }
+
+    /// Drops `username` from users, workspaces, shares and assignments, and
+    /// hands their tasks and projects to `heir` or deletes them. Must run
+    /// inside `update_tasks` with users freshly loaded; the caller saves
+    /// users.
+    pub(super) fn remove_user(&mut self, username: &str, heir: Option<&str>) -> Result<()> {
+        let sole_owner = self.workspaces.iter().any(|workspace| {
+            workspace.members.get(username) == Some(&Role::Owner)
+                && workspace.members.values().filter(|role| **role == Role::Owner).count() == 1
+        });
+        if sole_owner {
+            return Err(TodoError::Validation("User is the only owner of a workspace; transfer it first"));
+        }
+
+        match heir {
+            Some(heir) => {
+                let owned = |name: &str| self.projects.iter().any(|p| p.user_id == heir && p.name == name);
+                if self.projects.iter().any(|p| p.user_id == username && owned(&p.name)) {
+                    return Err(TodoError::DuplicateProject);
+                }
+                for project in self.projects.iter_mut().filter(|project| project.user_id == username) {
+                    project.user_id = heir.to_string();
+                }
+                for task in self.tasks.values_mut().chain(self.trash.values_mut()).chain(self.archive.values_mut()) {
+                    let inherited = task.user_id == username;
+                    let in_project = task.project_user() == username;
+                    if inherited {
+                        task.user_id = heir.to_string();
+                        task.shared_with.remove(heir);
+                    }
+                    if in_project {
+                        task.project_owner = Some(heir.to_string());
+                    }
+                    if inherited || in_project {
+                        // Back to `None` once the heir owns both the task and its project.
+                        task.project_owner = task.project_owner.take().filter(|owner| *owner != task.user_id);
+                        task.version += 1;
+                    }
+                }
+            }
+            None => {
+                self.projects.retain(|project| project.user_id != username);
+                self.trash.retain(|_, task| task.user_id != username);
+                self.archive.retain(|_, task| task.user_id != username);
+                let owned: Vec<u32> = self.tasks.values()
+                    .filter(|task| task.user_id == username)
+                    .map(|task| task.id)
+                    .collect();
+                for id in owned {
+                    self.tasks.remove(&id);
+                    self.forget_dependency(id);
+                }
+                for task in self.tasks.values_mut().chain(self.trash.values_mut()).chain(self.archive.values_mut()) {
+                    if task.project_user() == username {
+                        task.set_project(None);
+                        task.version += 1;
+                    }
+                }
+            }
+        }
+
+        // Trashed and archived tasks too, or a new account reusing the name
+        // would regain access once they are restored.
+        for task in self.tasks.values_mut().chain(self.trash.values_mut()).chain(self.archive.values_mut()) {
+            let unassign = task.assignee.as_deref() == Some(username);
+            if task.shared_with.remove(username).is_some() || unassign {
+                if unassign {
+                    task.assignee = None;
+                }
+                task.version += 1;
+            }
+        }
+        for project in &mut self.projects {
+            project.shared_with.remove(username);
+        }
+        for workspace in &mut self.workspaces {
+            workspace.members.remove(username);
+        }
+        self.users.remove(username);
+        Ok(())
+    }
+
+    /// Returns the logged-in admin's username, or fails if the current user
+    /// isn't an admin.
+    fn require_admin(&mut self) -> Result<String> {
+        let user_id = self.current_user.clone().ok_or(TodoError::NotLoggedIn)?;
+        self.load_users()?;
+        match self.users.get(&user_id) {
+            Some(user) if user.admin => Ok(user_id),
+            _ => Err(TodoError::Unauthorized),
+        }
+    }
+}
@@ -0,0 +1,134 @@
+use chrono::{DateTime, TimeDelta, Utc};
+
+use super::TodoApp;
+use crate::error::{Result, TodoError};
+use crate::model::{Permission, Task};
+
+impl TodoApp {
+    /// Sets how long after being closed a task is archived automatically, or
+    /// turns automatic archiving off when `None` (the default).
+    pub fn set_auto_archive(&mut self, after: Option<TimeDelta>) {
+        self.auto_archive = after;
+    }
+
+    /// Moves a done or cancelled task the current user owns, with its
+    /// subtasks, which they must own too, out of the task list into the
+    /// archive.
+    pub fn archive_task(&mut self, task_id: u32) -> Result<()> {
+        let user_id = self.current_user.clone().ok_or(TodoError::NotLoggedIn)?;
+        let seen = self.tasks.get(&task_id).map(|task| task.version);
+
+        self.update_tasks(|app| {
+            app.check_task(task_id, &user_id, Permission::Owner, seen)?;
+            let subtree: Vec<u32> = app.descendant_ids(task_id).into_iter().chain([task_id]).collect();
+            for &id in &subtree {
+                app.check_task(id, &user_id, Permission::Owner, None)?;
+            }
+            if subtree.iter().any(|id| app.tasks[id].is_open()) {
+                return Err(TodoError::Validation("Only done or cancelled tasks can be archived"));
+            }
+            app.move_to_archive(&subtree);
+            Ok(())
+        })
+    }
+
+    /// Archives the current user's top-level tasks that were closed more
+    /// than `age` ago, with their subtasks. Returns how many were archived.
+    pub fn archive_closed_before(&mut self, age: TimeDelta) -> Result<usize> {
+        let user_id = self.current_user.clone().ok_or(TodoError::NotLoggedIn)?;
+
+        self.update_tasks(|app| {
+            let before = app.archive.len();
+            app.archive_stale(Utc::now() - age, Some(&user_id));
+            Ok(app.archive.len() - before)
+        })
+    }
+
+    /// Returns the archived tasks the current user has access to whose title,
+    /// description or tags contain `query`, ignoring case. An empty query
+    /// matches all of them. Most recently archived first.
+    pub fn search_archive(&self, query: &str) -> Result<Vec<&Task>> {
+        let user_id = self.current_user.as_ref().ok_or(TodoError::NotLoggedIn)?;
+        let query = query.to_lowercase();
+
+        let mut tasks: Vec<&Task> = self.archive.values()
+            .filter(|task| self.permission(task, user_id).is_some())
+            .filter(|task| {
+                task.title.to_lowercase().contains(&query)
+                    || task.description.to_lowercase().contains(&query)
+                    || task.tags.iter().any(|tag| tag.contains(&query))
+            })
+            .collect();
+        tasks.sort_by(|a, b| b.archived_at.cmp(&a.archived_at).then(a.id.cmp(&b.id)));
+        Ok(tasks)
+    }
+
+    /// Moves an archived task, and the subtasks archived along with it, back
+    /// into the task list. It keeps its status; reopen it separately.
+    pub fn unarchive_task(&mut self, task_id: u32) -> Result<()> {
+        let user_id = self.current_user.clone().ok_or(TodoError::NotLoggedIn)?;
+
+        self.update_tasks(|app| {
+            let archived_at = app.archive.get(&task_id).ok_or(TodoError::TaskNotFound)?.archived_at;
+            let mut restore = vec![task_id];
+            let mut i = 0;
+            while i < restore.len() {
+                let parent = restore[i];
+                restore.extend(app.archive.values()
+                    .filter(|task| task.parent_id == Some(parent) && task.archived_at == archived_at)
+                    .map(|task| task.id));
+                i += 1;
+            }
+            let owned = |id: &u32| app.permission(&app.archive[id], &user_id) == Some(Permission::Owner);
+            if !restore.iter().all(owned) {
+                return Err(TodoError::Unauthorized);
+            }
+
+            for id in restore {
+                let Some(mut task) = app.archive.remove(&id) else { continue };
+                task.archived_at = None;
+                task.version += 1;
+                app.tasks.insert(id, task);
+            }
+            app.detach_orphan(task_id);
+            Ok(())
+        })
+    }
+
+    /// Archives every user's tasks that have been closed for longer than the
+    /// auto-archive period. Must run inside `update_tasks`.
+    pub(super) fn auto_archive(&mut self) {
+        if let Some(after) = self.auto_archive {
+            self.archive_stale(Utc::now() - after, None);
+        }
+    }
+
+    /// Archives closed top-level tasks (and their closed subtrees) that were
+    /// closed before `cutoff`, for one user or everyone.
+    fn archive_stale(&mut self, cutoff: DateTime<Utc>, user_id: Option<&str>) {
+        let roots: Vec<u32> = self.tasks.values()
+            .filter(|task| task.parent_id.is_none() && !task.is_open())
+            .filter(|task| user_id.is_none_or(|user_id| task.user_id == user_id))
+            .filter(|task| task.completed_at.or(task.cancelled_at).is_some_and(|at| at < cutoff))
+            .map(|task| task.id)
+            .collect();
+
+        for root in roots {
+            let subtree: Vec<u32> = self.descendant_ids(root).into_iter().chain([root]).collect();
+            if subtree.iter().all(|id| !self.tasks[id].is_open()) {
+                self.move_to_archive(&subtree);
+            }
+        }
+    }
+
+    fn move_to_archive(&mut self, ids: &[u32]) {
+        let now = Utc::now();
+        for id in ids {
+            if let Some(mut task) = self.tasks.remove(id) {
+                task.archived_at = Some(now);
+                task.version += 1;
+                self.archive.insert(*id, task);
+            }
+        }
+    }
+}
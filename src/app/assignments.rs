@@ -0,0 +1,66 @@
+use chrono::Utc;
+
+use super::TodoApp;
+use crate::error::{Result, TodoError};
+use crate::model::{Assignment, Permission, Task};
+
+impl TodoApp {
+    /// Assigns a task to `username`, or unassigns it when `None`. The
+    /// assignee can see, edit and complete the task, so assigning it to
+    /// someone else needs owner access; editors may only take it themselves
+    /// or unassign it. Every change is recorded in the task's assignment
+    /// history.
+    pub fn assign_task(&mut self, task_id: u32, username: Option<&str>) -> Result<()> {
+        let user_id = self.current_user.clone().ok_or(TodoError::NotLoggedIn)?;
+        if let Some(username) = username {
+            self.load_users()?;
+            if !self.users.contains_key(username) {
+                return Err(TodoError::UserNotFound);
+            }
+        }
+        let seen = self.tasks.get(&task_id).map(|task| task.version);
+
+        self.update_tasks(|app| {
+            let needed = match username {
+                Some(username) if username != user_id => Permission::Owner,
+                _ => Permission::Editor,
+            };
+            app.check_task(task_id, &user_id, needed, seen)?;
+            let task = app.tasks.get_mut(&task_id).ok_or(TodoError::TaskNotFound)?;
+            let to = username.map(str::to_string);
+            if task.assignee == to {
+                return Ok(());
+            }
+            task.assignments.push(Assignment {
+                from: task.assignee.take(),
+                to: to.clone(),
+                by: user_id,
+                at: Utc::now(),
+            });
+            task.assignee = to;
+            task.version += 1;
+            Ok(())
+        })
+    }
+
+    /// Returns the tasks currently assigned to the current user, in
+    /// [`list_tasks`](Self::list_tasks) order.
+    pub fn assigned_to_me(&self) -> Result<Vec<&Task>> {
+        let user_id = self.current_user.as_ref().ok_or(TodoError::NotLoggedIn)?;
+        Ok(self.list_tasks()?
+            .into_iter()
+            .filter(|task| task.assignee.as_ref() == Some(user_id))
+            .collect())
+    }
+
+    /// Returns the tasks the current user assigned to someone else and that
+    /// are still assigned that way, in [`list_tasks`](Self::list_tasks) order.
+    pub fn assigned_by_me(&self) -> Result<Vec<&Task>> {
+        let user_id = self.current_user.as_ref().ok_or(TodoError::NotLoggedIn)?;
+        Ok(self.list_tasks()?
+            .into_iter()
+            .filter(|task| task.assignee.as_ref().is_some_and(|assignee| assignee != user_id))
+            .filter(|task| task.assignments.last().is_some_and(|last| last.by == *user_id))
+            .collect())
+    }
+}
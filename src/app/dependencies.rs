@@ -0,0 +1,180 @@
+use super::TodoApp;
+use crate::error::{Result, TodoError};
+use crate::model::{Permission, Status, Task};
+
+impl TodoApp {
+    /// Whether `target` can be reached from `from` by following `blocked_by`
+    /// edges. Trashed and archived tasks are followed too, since their edges
+    /// come back with them when they are restored.
+    fn depends_on(&self, from: u32, target: u32) -> bool {
+        let mut stack = vec![from];
+        let mut seen = Vec::new();
+        while let Some(id) = stack.pop() {
+            if id == target {
+                return true;
+            }
+            if seen.contains(&id) {
+                continue;
+            }
+            seen.push(id);
+            let task = self.tasks.get(&id)
+                .or_else(|| self.trash.get(&id))
+                .or_else(|| self.archive.get(&id));
+            if let Some(task) = task {
+                stack.extend(task.blocked_by.iter().copied());
+            }
+        }
+        false
+    }
+
+    /// Records that `task_id` can't be completed before `blocker_id`. Both
+    /// must belong to the current user, and the dependency must not close a
+    /// cycle.
+    pub fn add_dependency(&mut self, task_id: u32, blocker_id: u32) -> Result<()> {
+        let user_id = self.current_user.clone().ok_or(TodoError::NotLoggedIn)?;
+        let seen = self.tasks.get(&task_id).map(|task| task.version);
+
+        self.update_tasks(|app| {
+            app.check_task(task_id, &user_id, Permission::Owner, seen)?;
+            app.check_task(blocker_id, &user_id, Permission::Owner, None)?;
+            if app.depends_on(blocker_id, task_id) {
+                return Err(TodoError::DependencyCycle);
+            }
+
+            let task = app.tasks.get_mut(&task_id).ok_or(TodoError::TaskNotFound)?;
+            if task.blocked_by.insert(blocker_id) {
+                task.version += 1;
+            }
+            Ok(())
+        })
+    }
+
+    /// Removes a dependency added with [`add_dependency`](Self::add_dependency).
+    pub fn remove_dependency(&mut self, task_id: u32, blocker_id: u32) -> Result<()> {
+        let user_id = self.current_user.clone().ok_or(TodoError::NotLoggedIn)?;
+        let seen = self.tasks.get(&task_id).map(|task| task.version);
+
+        self.update_tasks(|app| {
+            app.check_task(task_id, &user_id, Permission::Owner, seen)?;
+            let task = app.tasks.get_mut(&task_id).ok_or(TodoError::TaskNotFound)?;
+            if task.blocked_by.remove(&blocker_id) {
+                task.version += 1;
+            }
+            Ok(())
+        })
+    }
+
+    /// IDs of the tasks blocking `task` that are still open.
+    pub fn open_blockers(&self, task: &Task) -> Vec<u32> {
+        task.blocked_by.iter()
+            .copied()
+            .filter(|id| self.tasks.get(id).is_some_and(|blocker| blocker.is_open()))
+            .collect()
+    }
+
+    /// Whether `task` is open and waiting on another open task.
+    pub fn is_blocked(&self, task: &Task) -> bool {
+        task.is_open() && !self.open_blockers(task).is_empty()
+    }
+
+    /// Returns the current user's open tasks that can be worked on right now:
+    /// not marked blocked, no open blockers and no open subtasks. In
+    /// [`list_tasks`](Self::list_tasks) order.
+    pub fn next_actionable_tasks(&self) -> Result<Vec<&Task>> {
+        Ok(self.list_tasks()?
+            .into_iter()
+            .filter(|task| task.is_open() && task.status != Status::Blocked && !self.is_blocked(task))
+            .filter(|task| self.child_ids(task.id).iter().all(|id| !self.tasks[id].is_open()))
+            .collect())
+    }
+
+    /// Drops `task_id` from every task's blockers, e.g. after it was deleted.
+    pub(super) fn forget_dependency(&mut self, task_id: u32) {
+        for task in self.tasks.values_mut() {
+            if task.blocked_by.remove(&task_id) {
+                task.version += 1;
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::model::Priority;
+    use crate::storage::MemoryStorage;
+
+    fn app_with_tasks(count: u32) -> TodoApp {
+        let mut app = TodoApp::with_storage(Box::new(MemoryStorage::default()));
+        app.register("alice".into(), "secret".into()).unwrap();
+        app.login("alice".into(), "secret".into()).unwrap();
+        for i in 1..=count {
+            app.add_task(format!("task {}", i), String::new(), Priority::Normal, None).unwrap();
+        }
+        app
+    }
+
+    #[test]
+    fn depends_on_follows_transitive_edges() {
+        let mut app = app_with_tasks(4);
+        app.add_dependency(1, 2).unwrap();
+        app.add_dependency(2, 3).unwrap();
+
+        assert!(app.depends_on(1, 3));
+        assert!(app.depends_on(1, 1));
+        assert!(!app.depends_on(3, 1));
+        assert!(!app.depends_on(1, 4));
+    }
+
+    #[test]
+    fn rejects_self_dependency() {
+        let mut app = app_with_tasks(1);
+        assert!(matches!(app.add_dependency(1, 1), Err(TodoError::DependencyCycle)));
+    }
+
+    #[test]
+    fn rejects_direct_and_indirect_cycles() {
+        let mut app = app_with_tasks(3);
+        app.add_dependency(1, 2).unwrap();
+        assert!(matches!(app.add_dependency(2, 1), Err(TodoError::DependencyCycle)));
+
+        app.add_dependency(2, 3).unwrap();
+        assert!(matches!(app.add_dependency(3, 1), Err(TodoError::DependencyCycle)));
+        assert!(app.get_task(3).unwrap().blocked_by.is_empty());
+    }
+
+    #[test]
+    fn allows_diamonds() {
+        let mut app = app_with_tasks(4);
+        app.add_dependency(1, 2).unwrap();
+        app.add_dependency(1, 3).unwrap();
+        app.add_dependency(2, 4).unwrap();
+        app.add_dependency(3, 4).unwrap();
+
+        assert!(app.is_blocked(app.get_task(1).unwrap()));
+        let next: Vec<u32> = app.next_actionable_tasks().unwrap().iter().map(|task| task.id).collect();
+        assert_eq!(next, vec![4]);
+    }
+
+    #[test]
+    fn completing_blocker_unblocks() {
+        let mut app = app_with_tasks(2);
+        app.add_dependency(1, 2).unwrap();
+        assert!(matches!(app.complete_task(1), Err(TodoError::Blocked)));
+
+        app.complete_task(2).unwrap();
+        app.complete_task(1).unwrap();
+    }
+
+    #[test]
+    fn rejects_cycles_through_trashed_tasks() {
+        let mut app = app_with_tasks(3);
+        app.add_dependency(3, 1).unwrap();
+        app.add_dependency(1, 2).unwrap();
+        app.delete_task(1).unwrap();
+
+        assert!(matches!(app.add_dependency(2, 3), Err(TodoError::DependencyCycle)));
+        app.restore_task(1).unwrap();
+        assert!(!app.depends_on(2, 3));
+    }
+}
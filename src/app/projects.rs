@@ -0,0 +1,170 @@
+use chrono::Utc;
+
+use super::TodoApp;
+use crate::error::{Result, TodoError};
+use crate::model::{Permission, Progress, Project, Status, Task};
+
+/// Trims each `/`-separated segment of a project name and rejects empty ones.
+fn normalize_name(name: &str) -> Result<String> {
+    let segments: Vec<&str> = name.split('/').map(str::trim).collect();
+    if segments.iter().any(|segment| segment.is_empty()) {
+        return Err(TodoError::Validation("Project name must not be empty or contain empty segments"));
+    }
+    Ok(segments.join("/"))
+}
+
+impl TodoApp {
+    pub(super) fn has_project(&self, user_id: &str, name: &str) -> bool {
+        self.projects.iter().any(|project| project.user_id == user_id && project.name == name)
+    }
+
+    /// Finds the project called `name` that `user_id` has at least `needed`
+    /// on, preferring their own over one reached through a workspace or share.
+    pub(super) fn find_project(&self, user_id: &str, name: &str, needed: Permission) -> Result<&Project> {
+        self.projects.iter()
+            .filter(|project| project.name == name)
+            .filter(|project| self.project_permission(project, user_id).is_some_and(|permission| permission >= needed))
+            .min_by_key(|project| project.user_id != user_id)
+            .ok_or(TodoError::ProjectNotFound)
+    }
+
+    /// Creates a project for the current user. For a nested name such as
+    /// `work/client-a`, the parent project must already exist.
+    pub fn create_project(&mut self, name: &str) -> Result<()> {
+        let user_id = self.current_user.clone().ok_or(TodoError::NotLoggedIn)?;
+        let name = normalize_name(name)?;
+
+        self.update_tasks(|app| {
+            if app.has_project(&user_id, &name) {
+                return Err(TodoError::DuplicateProject);
+            }
+            let project = Project { name, user_id, created_at: Utc::now(), shared_with: Default::default(), workspace: None };
+            if let Some(parent) = project.parent() {
+                if !app.has_project(&project.user_id, parent) {
+                    return Err(TodoError::ProjectNotFound);
+                }
+            }
+            app.projects.push(project);
+            Ok(())
+        })
+    }
+
+    /// Renames or re-parents a project, carrying its subprojects and tasks,
+    /// including trashed and archived ones, along.
+    pub fn rename_project(&mut self, name: &str, new_name: &str) -> Result<()> {
+        let user_id = self.current_user.clone().ok_or(TodoError::NotLoggedIn)?;
+        let new_name = normalize_name(new_name)?;
+
+        self.update_tasks(|app| {
+            let old = app.projects.iter()
+                .find(|project| project.user_id == user_id && project.name == name)
+                .cloned()
+                .ok_or(TodoError::ProjectNotFound)?;
+            if app.has_project(&user_id, &new_name) {
+                return Err(TodoError::DuplicateProject);
+            }
+            if old.contains(&new_name) {
+                return Err(TodoError::Validation("A project can't be moved into itself"));
+            }
+            if let Some((parent, _)) = new_name.rsplit_once('/') {
+                if !app.has_project(&user_id, parent) {
+                    return Err(TodoError::ProjectNotFound);
+                }
+            }
+
+            let renamed = |current: &str| format!("{}{}", new_name, &current[old.name.len()..]);
+            for project in app.projects.iter_mut().filter(|project| project.user_id == user_id) {
+                if old.contains(&project.name) {
+                    project.name = renamed(&project.name);
+                }
+            }
+            let all_tasks = app.tasks.values_mut().chain(app.trash.values_mut()).chain(app.archive.values_mut());
+            for task in all_tasks.filter(|task| task.project_user() == user_id) {
+                if let Some(project) = task.project.as_mut().filter(|project| old.contains(project)) {
+                    *project = renamed(project);
+                    task.version += 1;
+                }
+            }
+            Ok(())
+        })
+    }
+
+    /// Deletes a project that has no subprojects. Its tasks, including trashed
+    /// and archived ones, are kept and no longer belong to any project.
+    pub fn delete_project(&mut self, name: &str) -> Result<()> {
+        let user_id = self.current_user.clone().ok_or(TodoError::NotLoggedIn)?;
+
+        self.update_tasks(|app| {
+            if !app.has_project(&user_id, name) {
+                return Err(TodoError::ProjectNotFound);
+            }
+            if app.projects.iter().any(|project| project.user_id == user_id && project.parent() == Some(name)) {
+                return Err(TodoError::Validation("Project has subprojects; delete or move them first"));
+            }
+
+            app.projects.retain(|project| !(project.user_id == user_id && project.name == name));
+            let all_tasks = app.tasks.values_mut().chain(app.trash.values_mut()).chain(app.archive.values_mut());
+            for task in all_tasks.filter(|task| task.project_user() == user_id) {
+                if task.project.as_deref() == Some(name) {
+                    task.set_project(None);
+                    task.version += 1;
+                }
+            }
+            Ok(())
+        })
+    }
+
+    /// Returns the projects the current user has access to, sorted by name
+    /// so subprojects follow their parent. Where several share a name, only
+    /// the one [`project_tasks`](Self::project_tasks) would pick is listed.
+    pub fn list_projects(&self) -> Result<Vec<&Project>> {
+        let user_id = self.current_user.as_ref().ok_or(TodoError::NotLoggedIn)?;
+
+        let mut projects: Vec<&Project> = self.projects.iter()
+            .filter(|project| self.project_permission(project, user_id).is_some())
+            .collect();
+        projects.sort_by(|a, b| a.name.cmp(&b.name).then((a.user_id != *user_id).cmp(&(b.user_id != *user_id))));
+        projects.dedup_by(|a, b| a.name == b.name);
+        Ok(projects)
+    }
+
+    /// Moves one of the current user's tasks into `project`, which they
+    /// need at least editor access to, or out of any project when `None`.
+    pub fn move_task(&mut self, task_id: u32, project: Option<&str>) -> Result<()> {
+        let user_id = self.current_user.clone().ok_or(TodoError::NotLoggedIn)?;
+        let seen = self.tasks.get(&task_id).map(|task| task.version);
+
+        self.update_tasks(|app| {
+            app.check_task(task_id, &user_id, Permission::Owner, seen)?;
+            let project = project
+                .map(|name| app.find_project(&user_id, name, Permission::Editor).cloned())
+                .transpose()?;
+            let task = app.tasks.get_mut(&task_id).ok_or(TodoError::TaskNotFound)?;
+            task.set_project(project.as_ref());
+            task.version += 1;
+            Ok(())
+        })
+    }
+
+    /// Returns the tasks in a project and its subprojects, in
+    /// [`list_tasks`](Self::list_tasks) order.
+    pub fn project_tasks(&self, name: &str) -> Result<Vec<&Task>> {
+        let user_id = self.current_user.as_ref().ok_or(TodoError::NotLoggedIn)?;
+        let project = self.find_project(user_id, name, Permission::Viewer)?;
+
+        Ok(self.list_tasks()?
+            .into_iter()
+            .filter(|task| task.in_project(project))
+            .collect())
+    }
+
+    /// Counts done and total tasks in a project and its subprojects.
+    /// Cancelled tasks don't count towards either.
+    pub fn project_progress(&self, name: &str) -> Result<Progress> {
+        let tasks = self.project_tasks(name)?;
+        Ok(Progress {
+            done: tasks.iter().filter(|task| task.status == Status::Done).count(),
+            total: tasks.iter().filter(|task| task.status != Status::Cancelled).count(),
+        })
+    }
+}
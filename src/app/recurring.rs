@@ -0,0 +1,45 @@
+use chrono::{Local, Utc};
+
+use super::TodoApp;
+use crate::error::{Result, TodoError};
+use crate::model::Status;
+use crate::recurrence::Recurrence;
+
+impl TodoApp {
+    /// Makes a task the current user can edit repeat, or stops it repeating
+    /// when `None`.
+    pub fn set_recurrence(&mut self, task_id: u32, recurrence: Option<Recurrence>) -> Result<()> {
+        if let Some(recurrence) = &recurrence {
+            recurrence.validate().map_err(TodoError::Validation)?;
+        }
+        self.modify_task(task_id, |task| task.recurrence = recurrence)
+    }
+
+    /// Creates the next occurrence of the just-completed recurring task
+    /// `task_id` and returns its ID. The rule moves to the new occurrence, so
+    /// reopening and completing the old one doesn't spawn another. Must run
+    /// inside `update_tasks`.
+    pub(super) fn spawn_next_occurrence(&mut self, task_id: u32) -> Result<Option<u32>> {
+        let task = self.tasks.get_mut(&task_id).ok_or(TodoError::TaskNotFound)?;
+        let Some(recurrence) = task.recurrence.take() else {
+            return Ok(None);
+        };
+
+        let mut next = task.clone();
+        next.recurrence = Some(recurrence.clone());
+        next.id = self.next_task_id;
+        next.status = Status::Todo;
+        next.started_at = None;
+        next.completed_at = None;
+        next.cancelled_at = None;
+        next.created_at = Utc::now();
+        next.version = 0;
+        next.blocked_by.clear();
+        next.assignments.clear();
+        next.set_due(Some(recurrence.next_due(task.due(), Local::now())));
+
+        self.next_task_id += 1;
+        self.tasks.insert(next.id, next);
+        Ok(Some(self.next_task_id - 1))
+    }
+}
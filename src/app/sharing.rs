@@ -0,0 +1,101 @@
+use super::TodoApp;
+use crate::error::{Result, TodoError};
+use crate::model::{Permission, Project, Task};
+
+impl TodoApp {
+    /// What `user_id` may do with `task`: [`Permission::Owner`] for its
+    /// creator, otherwise the highest level given by being its assignee
+    /// ([`Permission::Editor`]), by their role in the task's workspace, by a
+    /// share on the task, or by their access to its project (see
+    /// [`project_permission`](Self::project_permission)).
+    pub fn permission(&self, task: &Task, user_id: &str) -> Option<Permission> {
+        if task.user_id == user_id {
+            return Some(Permission::Owner);
+        }
+
+        let as_assignee = (task.assignee.as_deref() == Some(user_id)).then_some(Permission::Editor);
+        let via_workspace = task.workspace.as_ref()
+            .and_then(|workspace| self.role(workspace, user_id))
+            .map(|role| role.task_permission());
+        let via_project = self.projects.iter()
+            .find(|project| project.user_id == task.project_user() && task.project.as_ref() == Some(&project.name))
+            .and_then(|project| self.project_permission(project, user_id));
+        [as_assignee, via_workspace, via_project, task.shared_with.get(user_id).copied()]
+            .into_iter()
+            .flatten()
+            .max()
+    }
+
+    /// What `user_id` may do with the tasks in `project`:
+    /// [`Permission::Owner`] for its creator, otherwise the highest level
+    /// given by a share on the project or one of its ancestors, or by their
+    /// role in a workspace one of those belongs to.
+    pub fn project_permission(&self, project: &Project, user_id: &str) -> Option<Permission> {
+        if project.user_id == user_id {
+            return Some(Permission::Owner);
+        }
+
+        self.projects.iter()
+            .filter(|ancestor| ancestor.user_id == project.user_id && ancestor.contains(&project.name))
+            .flat_map(|ancestor| {
+                let via_workspace = ancestor.workspace.as_ref()
+                    .and_then(|workspace| self.role(workspace, user_id))
+                    .map(|role| role.task_permission());
+                [via_workspace, ancestor.shared_with.get(user_id).copied()]
+            })
+            .flatten()
+            .max()
+    }
+
+    /// Gives `username` viewer or editor access to one of the current user's
+    /// tasks, or takes it away when `None`.
+    pub fn share_task(&mut self, task_id: u32, username: &str, permission: Option<Permission>) -> Result<()> {
+        self.check_share_target(username, permission)?;
+        let user_id = self.current_user.clone().ok_or(TodoError::NotLoggedIn)?;
+        let seen = self.tasks.get(&task_id).map(|task| task.version);
+
+        self.update_tasks(|app| {
+            app.check_task(task_id, &user_id, Permission::Owner, seen)?;
+            let task = app.tasks.get_mut(&task_id).ok_or(TodoError::TaskNotFound)?;
+            match permission {
+                Some(permission) => task.shared_with.insert(username.to_string(), permission),
+                None => task.shared_with.remove(username),
+            };
+            task.version += 1;
+            Ok(())
+        })
+    }
+
+    /// Gives `username` viewer or editor access to every task in one of the
+    /// current user's projects and its subprojects, or takes it away when
+    /// `None`.
+    pub fn share_project(&mut self, name: &str, username: &str, permission: Option<Permission>) -> Result<()> {
+        self.check_share_target(username, permission)?;
+        let user_id = self.current_user.clone().ok_or(TodoError::NotLoggedIn)?;
+
+        self.update_tasks(|app| {
+            let project = app.projects.iter_mut()
+                .find(|project| project.user_id == user_id && project.name == name)
+                .ok_or(TodoError::ProjectNotFound)?;
+            match permission {
+                Some(permission) => project.shared_with.insert(username.to_string(), permission),
+                None => project.shared_with.remove(username),
+            };
+            Ok(())
+        })
+    }
+
+    fn check_share_target(&mut self, username: &str, permission: Option<Permission>) -> Result<()> {
+        if permission == Some(Permission::Owner) {
+            return Err(TodoError::Validation("Ownership can't be shared"));
+        }
+        if self.current_user.as_deref() == Some(username) {
+            return Err(TodoError::Validation("Can't share with yourself"));
+        }
+        self.load_users()?;
+        if !self.users.contains_key(username) {
+            return Err(TodoError::UserNotFound);
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,47 @@
+use super::TodoApp;
+use crate::error::{Result, TodoError};
+use crate::model::Permission;
+
+impl TodoApp {
+    /// IDs of the direct children of `task_id`.
+    pub(super) fn child_ids(&self, task_id: u32) -> Vec<u32> {
+        self.tasks.values()
+            .filter(|task| task.parent_id == Some(task_id))
+            .map(|task| task.id)
+            .collect()
+    }
+
+    /// IDs of every task below `task_id` in the subtask tree.
+    pub(super) fn descendant_ids(&self, task_id: u32) -> Vec<u32> {
+        let mut found = self.child_ids(task_id);
+        let mut i = 0;
+        while i < found.len() {
+            found.extend(self.child_ids(found[i]));
+            i += 1;
+        }
+        found
+    }
+
+    /// Makes one of the current user's tasks a subtask of `parent`, or a
+    /// top-level task when `None`. The parent must also belong to the current
+    /// user and can't be the task itself or one of its subtasks.
+    pub fn set_parent(&mut self, task_id: u32, parent: Option<u32>) -> Result<()> {
+        let user_id = self.current_user.clone().ok_or(TodoError::NotLoggedIn)?;
+        let seen = self.tasks.get(&task_id).map(|task| task.version);
+
+        self.update_tasks(|app| {
+            app.check_task(task_id, &user_id, Permission::Owner, seen)?;
+            if let Some(parent_id) = parent {
+                app.check_task(parent_id, &user_id, Permission::Owner, None)?;
+                if parent_id == task_id || app.descendant_ids(task_id).contains(&parent_id) {
+                    return Err(TodoError::Validation("A task can't be nested under itself or its subtasks"));
+                }
+            }
+
+            let task = app.tasks.get_mut(&task_id).ok_or(TodoError::TaskNotFound)?;
+            task.parent_id = parent;
+            task.version += 1;
+            Ok(())
+        })
+    }
+}
@@ -0,0 +1,65 @@
+use std::collections::{BTreeMap, BTreeSet};
+
+use super::TodoApp;
+use crate::error::{Result, TodoError};
+use crate::model::Task;
+
+/// Lowercases and trims a tag, rejecting empty tags and tags with whitespace
+/// or commas.
+fn normalize_tag(tag: &str) -> Result<String> {
+    let tag = tag.trim().to_lowercase();
+    if tag.is_empty() {
+        return Err(TodoError::Validation("Tag must not be empty"));
+    }
+    if tag.contains(|c: char| c.is_whitespace() || c == ',') {
+        return Err(TodoError::Validation("Tag must not contain spaces or commas"));
+    }
+    Ok(tag)
+}
+
+pub(super) fn normalize_tags<'a>(tags: impl IntoIterator<Item = &'a str>) -> Result<BTreeSet<String>> {
+    tags.into_iter().map(normalize_tag).collect()
+}
+
+impl TodoApp {
+    /// Adds tags to a task the current user can edit.
+    pub fn tag_task<'a>(&mut self, task_id: u32, tags: impl IntoIterator<Item = &'a str>) -> Result<()> {
+        let tags = normalize_tags(tags)?;
+        self.modify_task(task_id, |task| task.tags.extend(tags))
+    }
+
+    /// Removes tags from a task the current user can edit. Tags the task
+    /// doesn't have are ignored.
+    pub fn untag_task<'a>(&mut self, task_id: u32, tags: impl IntoIterator<Item = &'a str>) -> Result<()> {
+        let tags = normalize_tags(tags)?;
+        self.modify_task(task_id, |task| task.tags.retain(|tag| !tags.contains(tag)))
+    }
+
+    /// Like [`list_tasks`](Self::list_tasks), keeping only tasks that carry
+    /// every tag in `include` and none in `exclude`.
+    pub fn list_tasks_tagged<'a>(
+        &self,
+        include: impl IntoIterator<Item = &'a str>,
+        exclude: impl IntoIterator<Item = &'a str>,
+    ) -> Result<Vec<&Task>> {
+        let include = normalize_tags(include)?;
+        let exclude = normalize_tags(exclude)?;
+
+        Ok(self.list_tasks()?
+            .into_iter()
+            .filter(|task| include.is_subset(&task.tags) && task.tags.is_disjoint(&exclude))
+            .collect())
+    }
+
+    /// Returns every tag the current user has used, with the number of tasks
+    /// carrying it.
+    pub fn tag_counts(&self) -> Result<BTreeMap<&str, usize>> {
+        let mut counts = BTreeMap::new();
+        for task in self.list_tasks()? {
+            for tag in &task.tags {
+                *counts.entry(tag.as_str()).or_default() += 1;
+            }
+        }
+        Ok(counts)
+    }
+}
@@ -0,0 +1,196 @@
+use chrono::{TimeDelta, Utc};
+
+use super::TodoApp;
+use crate::error::{Result, TodoError};
+use crate::model::{Permission, Task};
+
+impl TodoApp {
+    /// Sets how long deleted tasks stay in the trash before they are purged
+    /// for good, or keeps them until purged by hand when `None`. Defaults to
+    /// 30 days.
+    pub fn set_trash_retention(&mut self, retention: Option<TimeDelta>) {
+        self.trash_retention = retention;
+    }
+
+    /// Returns the deleted tasks the current user has access to, most
+    /// recently deleted first.
+    pub fn list_trash(&self) -> Result<Vec<&Task>> {
+        let user_id = self.current_user.as_ref().ok_or(TodoError::NotLoggedIn)?;
+
+        let mut tasks: Vec<&Task> = self.trash.values()
+            .filter(|task| self.permission(task, user_id).is_some())
+            .collect();
+        tasks.sort_by(|a, b| b.deleted_at.cmp(&a.deleted_at).then(a.id.cmp(&b.id)));
+        Ok(tasks)
+    }
+
+    /// Moves a deleted task, and the subtasks deleted along with it, back out
+    /// of the trash. A task whose parent or project no longer exists comes
+    /// back as a top-level task outside any project.
+    pub fn restore_task(&mut self, task_id: u32) -> Result<()> {
+        let user_id = self.current_user.clone().ok_or(TodoError::NotLoggedIn)?;
+
+        self.update_tasks(|app| {
+            let subtree = app.trashed_subtree(task_id);
+            for &id in &subtree {
+                app.check_trashed(id, &user_id, Permission::Editor)?;
+            }
+            for id in subtree {
+                let Some(mut task) = app.trash.remove(&id) else { continue };
+                task.deleted_at = None;
+                task.version += 1;
+                app.tasks.insert(id, task);
+            }
+
+            app.detach_orphan(task_id);
+            Ok(())
+        })
+    }
+
+    /// Permanently removes a deleted task the current user owns with the
+    /// subtasks deleted along with it, or every such task when `task_id` is
+    /// `None`. Returns how many tasks were purged.
+    pub fn purge_trash(&mut self, task_id: Option<u32>) -> Result<usize> {
+        let user_id = self.current_user.clone().ok_or(TodoError::NotLoggedIn)?;
+
+        self.update_tasks(|app| {
+            let ids: Vec<u32> = match task_id {
+                Some(id) => {
+                    let subtree = app.trashed_subtree(id);
+                    for &id in &subtree {
+                        app.check_trashed(id, &user_id, Permission::Owner)?;
+                    }
+                    subtree
+                }
+                None => app.trash.values()
+                    .filter(|task| app.permission(task, &user_id) == Some(Permission::Owner))
+                    .map(|task| task.id)
+                    .collect(),
+            };
+            for &id in &ids {
+                app.trash.remove(&id);
+                app.forget_dependency(id);
+            }
+            Ok(ids.len())
+        })
+    }
+
+    /// Moves `task_id` and its subtasks into the trash. Must run inside
+    /// `update_tasks`.
+    pub(super) fn trash_task(&mut self, task_id: u32) {
+        let now = Utc::now();
+        for id in self.descendant_ids(task_id).into_iter().chain([task_id]) {
+            if let Some(mut task) = self.tasks.remove(&id) {
+                task.deleted_at = Some(now);
+                task.version += 1;
+                self.trash.insert(id, task);
+            }
+        }
+    }
+
+    /// `task_id` followed by its subtasks that were trashed at the same time.
+    fn trashed_subtree(&self, task_id: u32) -> Vec<u32> {
+        let deleted_at = self.trash.get(&task_id).and_then(|task| task.deleted_at);
+        let mut subtree = vec![task_id];
+        let mut i = 0;
+        while i < subtree.len() {
+            let parent = subtree[i];
+            subtree.extend(self.trash.values()
+                .filter(|task| task.parent_id == Some(parent) && task.deleted_at == deleted_at)
+                .map(|task| task.id));
+            i += 1;
+        }
+        subtree
+    }
+
+    /// Drops every user's trashed tasks that are past the retention period.
+    /// Must run inside `update_tasks`.
+    pub(super) fn purge_expired_trash(&mut self) {
+        let Some(retention) = self.trash_retention else {
+            return;
+        };
+        let cutoff = Utc::now() - retention;
+        let expired: Vec<u32> = self.trash.values()
+            .filter(|task| task.deleted_at.is_some_and(|at| at < cutoff))
+            .map(|task| task.id)
+            .collect();
+        for id in expired {
+            self.trash.remove(&id);
+            self.forget_dependency(id);
+        }
+    }
+
+    /// Makes `task_id` top-level if its parent is gone and takes it out of
+    /// its project if that was deleted, e.g. after bringing it back from the
+    /// trash or the archive.
+    pub(super) fn detach_orphan(&mut self, task_id: u32) {
+        let Some(task) = self.tasks.get(&task_id) else { return };
+        let parent_gone = task.parent_id.is_some_and(|id| !self.tasks.contains_key(&id));
+        let project_gone = task.project.as_ref().is_some_and(|name| {
+            !self.projects.iter().any(|project| project.user_id == task.project_user() && project.name == *name)
+        });
+
+        let task = self.tasks.get_mut(&task_id).expect("checked above");
+        if parent_gone {
+            task.parent_id = None;
+        }
+        if project_gone {
+            task.set_project(None);
+        }
+    }
+
+    /// Like `check_task`, for a task in the trash.
+    fn check_trashed(&self, task_id: u32, user_id: &str, needed: Permission) -> Result<()> {
+        let task = self.trash.get(&task_id).ok_or(TodoError::TaskNotFound)?;
+        if self.permission(task, user_id).is_none_or(|permission| permission < needed) {
+            return Err(TodoError::Unauthorized);
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fs;
+
+    use crate::app::tests::{json_app, temp_dir};
+    use crate::app::TaskOptions;
+    use crate::model::Priority;
+
+    #[test]
+    fn failed_restore_leaves_task_in_trash() {
+        let dir = temp_dir("failed-restore");
+        let mut app = json_app(&dir);
+        let id = app.add_task("task".into(), String::new(), Priority::Normal, None).unwrap();
+        app.delete_task(id).unwrap();
+
+        // A directory in the way makes writing tasks.json fail.
+        fs::create_dir(dir.join("tasks.json.tmp")).unwrap();
+        assert!(app.restore_task(id).is_err());
+        fs::remove_dir(dir.join("tasks.json.tmp")).unwrap();
+
+        let mut app = json_app(&dir);
+        assert_eq!(app.list_trash().unwrap().len(), 1);
+        app.restore_task(id).unwrap();
+        assert!(app.get_task(id).is_ok());
+        fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn purging_a_task_purges_subtasks_deleted_with_it() {
+        let dir = temp_dir("purge-subtree");
+        let mut app = json_app(&dir);
+        let other = app.add_task("other".into(), String::new(), Priority::Normal, None).unwrap();
+        let root = app.add_task("root".into(), String::new(), Priority::Normal, None).unwrap();
+        let options = |parent| TaskOptions { parent: Some(parent), ..Default::default() };
+        let child = app.create_task("child".into(), String::new(), Priority::Normal, None, options(root)).unwrap();
+        app.create_task("grandchild".into(), String::new(), Priority::Normal, None, options(child)).unwrap();
+        app.delete_task(other).unwrap();
+        app.delete_task(root).unwrap();
+
+        assert_eq!(app.purge_trash(Some(root)).unwrap(), 3);
+        let left: Vec<u32> = app.list_trash().unwrap().iter().map(|task| task.id).collect();
+        assert_eq!(left, vec![other]);
+        fs::remove_dir_all(dir).unwrap();
+    }
+}
@@ -0,0 +1,199 @@
+use chrono::Utc;
+
+use super::TodoApp;
+use crate::error::{Result, TodoError};
+use crate::model::{Permission, Role, Task, Workspace};
+
+impl TodoApp {
+    /// `user_id`'s role in the workspace called `name`, if they are a member.
+    pub fn role(&self, name: &str, user_id: &str) -> Option<Role> {
+        self.workspaces.iter()
+            .find(|workspace| workspace.name == name)
+            .and_then(|workspace| workspace.members.get(user_id).copied())
+    }
+
+    /// Creates a workspace with the current user as its owner.
+    pub fn create_workspace(&mut self, name: &str) -> Result<()> {
+        let user_id = self.current_user.clone().ok_or(TodoError::NotLoggedIn)?;
+        let name = name.trim();
+        if name.is_empty() {
+            return Err(TodoError::Validation("Workspace name must not be empty"));
+        }
+
+        self.update_tasks(|app| {
+            if app.workspaces.iter().any(|workspace| workspace.name == name) {
+                return Err(TodoError::DuplicateWorkspace);
+            }
+            app.workspaces.push(Workspace {
+                name: name.to_string(),
+                created_at: Utc::now(),
+                members: [(user_id, Role::Owner)].into(),
+            });
+            Ok(())
+        })
+    }
+
+    /// Deletes a workspace the current user owns. Its tasks, including
+    /// trashed and archived ones, and projects go back to being private to
+    /// their creators.
+    pub fn delete_workspace(&mut self, name: &str) -> Result<()> {
+        let user_id = self.current_user.clone().ok_or(TodoError::NotLoggedIn)?;
+
+        self.update_tasks(|app| {
+            app.check_role(name, &user_id, Role::Owner)?;
+            app.workspaces.retain(|workspace| workspace.name != name);
+            let all_tasks = app.tasks.values_mut().chain(app.trash.values_mut()).chain(app.archive.values_mut());
+            for task in all_tasks.filter(|task| task.workspace.as_deref() == Some(name)) {
+                task.workspace = None;
+                task.version += 1;
+            }
+            for project in app.projects.iter_mut().filter(|project| project.workspace.as_deref() == Some(name)) {
+                project.workspace = None;
+            }
+            Ok(())
+        })
+    }
+
+    /// Returns the workspaces the current user belongs to, sorted by name.
+    pub fn list_workspaces(&self) -> Result<Vec<&Workspace>> {
+        let user_id = self.current_user.as_ref().ok_or(TodoError::NotLoggedIn)?;
+
+        let mut workspaces: Vec<&Workspace> = self.workspaces.iter()
+            .filter(|workspace| workspace.members.contains_key(user_id))
+            .collect();
+        workspaces.sort_by(|a, b| a.name.cmp(&b.name));
+        Ok(workspaces)
+    }
+
+    /// Adds `username` to a workspace with `role`, or changes their role if
+    /// they are already a member. Admins can manage members and guests; only
+    /// owners can grant or take away ownership.
+    pub fn set_member(&mut self, name: &str, username: &str, role: Role) -> Result<()> {
+        let user_id = self.current_user.clone().ok_or(TodoError::NotLoggedIn)?;
+        self.load_users()?;
+        if !self.users.contains_key(username) {
+            return Err(TodoError::UserNotFound);
+        }
+
+        self.update_tasks(|app| {
+            app.check_member_change(name, &user_id, username, Some(role))?;
+            let workspace = app.workspace_mut(name)?;
+            workspace.members.insert(username.to_string(), role);
+            if !workspace.members.values().any(|role| *role == Role::Owner) {
+                return Err(TodoError::Validation("A workspace must keep at least one owner"));
+            }
+            Ok(())
+        })
+    }
+
+    /// Removes `username` from a workspace. Members can always leave; removing
+    /// someone else follows the rules of [`set_member`](Self::set_member).
+    pub fn remove_member(&mut self, name: &str, username: &str) -> Result<()> {
+        let user_id = self.current_user.clone().ok_or(TodoError::NotLoggedIn)?;
+
+        self.update_tasks(|app| {
+            if username != user_id {
+                app.check_member_change(name, &user_id, username, None)?;
+            }
+            let workspace = app.workspace_mut(name)?;
+            if workspace.members.remove(username).is_none() {
+                return Err(TodoError::UserNotFound);
+            }
+            if !workspace.members.values().any(|role| *role == Role::Owner) {
+                return Err(TodoError::Validation("A workspace must keep at least one owner"));
+            }
+            Ok(())
+        })
+    }
+
+    /// Moves a task the current user owns into a workspace they are at least
+    /// a member of, or back out of any workspace when `None`.
+    pub fn move_task_to_workspace(&mut self, task_id: u32, workspace: Option<&str>) -> Result<()> {
+        let user_id = self.current_user.clone().ok_or(TodoError::NotLoggedIn)?;
+        let seen = self.tasks.get(&task_id).map(|task| task.version);
+
+        self.update_tasks(|app| {
+            app.check_task(task_id, &user_id, Permission::Owner, seen)?;
+            if let Some(name) = workspace {
+                app.check_role(name, &user_id, Role::Member)?;
+            }
+            let task = app.tasks.get_mut(&task_id).ok_or(TodoError::TaskNotFound)?;
+            task.workspace = workspace.map(str::to_string);
+            task.version += 1;
+            Ok(())
+        })
+    }
+
+    /// Moves one of the current user's projects, with its subprojects, into a
+    /// workspace they are at least a member of, or back out when `None`.
+    /// Workspace roles then apply to every task in those projects.
+    pub fn move_project_to_workspace(&mut self, name: &str, workspace: Option<&str>) -> Result<()> {
+        let user_id = self.current_user.clone().ok_or(TodoError::NotLoggedIn)?;
+
+        self.update_tasks(|app| {
+            if let Some(workspace) = workspace {
+                app.check_role(workspace, &user_id, Role::Member)?;
+            }
+            let root = app.projects.iter()
+                .find(|project| project.user_id == user_id && project.name == name)
+                .cloned()
+                .ok_or(TodoError::ProjectNotFound)?;
+            for project in app.projects.iter_mut().filter(|project| project.user_id == user_id) {
+                if root.contains(&project.name) {
+                    project.workspace = workspace.map(str::to_string);
+                }
+            }
+            Ok(())
+        })
+    }
+
+    /// Returns the tasks in a workspace the current user belongs to, either
+    /// directly or through a project, in [`list_tasks`](Self::list_tasks)
+    /// order.
+    pub fn workspace_tasks(&self, name: &str) -> Result<Vec<&Task>> {
+        let user_id = self.current_user.as_ref().ok_or(TodoError::NotLoggedIn)?;
+        self.check_role(name, user_id, Role::Guest)?;
+
+        Ok(self.list_tasks()?
+            .into_iter()
+            .filter(|task| self.task_workspaces(task).any(|workspace| workspace == name))
+            .collect())
+    }
+
+    /// The workspaces `task` is in: its own and those of the projects
+    /// containing it.
+    pub(super) fn task_workspaces<'a>(&'a self, task: &'a Task) -> impl Iterator<Item = &'a str> {
+        let via_project = self.projects.iter()
+            .filter(|project| task.in_project(project))
+            .filter_map(|project| project.workspace.as_deref());
+        task.workspace.as_deref().into_iter().chain(via_project)
+    }
+
+    fn workspace_mut(&mut self, name: &str) -> Result<&mut Workspace> {
+        self.workspaces.iter_mut()
+            .find(|workspace| workspace.name == name)
+            .ok_or(TodoError::WorkspaceNotFound)
+    }
+
+    /// Checks that `user_id` has at least `needed` in the workspace `name`.
+    pub(super) fn check_role(&self, name: &str, user_id: &str, needed: Role) -> Result<()> {
+        if !self.workspaces.iter().any(|workspace| workspace.name == name) {
+            return Err(TodoError::WorkspaceNotFound);
+        }
+        match self.role(name, user_id) {
+            Some(role) if role >= needed => Ok(()),
+            _ => Err(TodoError::Unauthorized),
+        }
+    }
+
+    /// Checks that `user_id` may change `username`'s membership to `role`
+    /// (`None` to remove them).
+    fn check_member_change(&self, name: &str, user_id: &str, username: &str, role: Option<Role>) -> Result<()> {
+        self.check_role(name, user_id, Role::Admin)?;
+        let touches_owner = role == Some(Role::Owner) || self.role(name, username) == Some(Role::Owner);
+        if touches_owner {
+            self.check_role(name, user_id, Role::Owner)?;
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,38 @@
+use argon2::{Argon2, PasswordHash, PasswordHasher, PasswordVerifier};
+use argon2::password_hash::{rand_core::OsRng, SaltString};
+
+use crate::error::Result;
+
+/// A hash made with the same parameters as [`hash_password`], checked
+/// against when a username doesn't exist so that takes as long as a wrong
+/// password.
+pub(crate) const DUMMY_HASH: &str = "$argon2id$v=19$m=19456,t=2,p=1$1LLj+7esgUOfMP7XHYbdHQ$eOroOG4PA3JCO3eMJTVgt7nemebR/171jwnjjuyvo20";
+
+pub(crate) fn hash_password(password: &str) -> Result<String> {
+    let salt = SaltString::generate(&mut OsRng);
+    Ok(Argon2::default().hash_password(password.as_bytes(), &salt)?.to_string())
+}
+
+fn constant_time_eq(a: &[u8], b: &[u8]) -> bool {
+    if a.len() != b.len() {
+        return false;
+    }
+    a.iter().zip(b).fold(0u8, |acc, (x, y)| acc | (x ^ y)) == 0
+}
+
+pub(crate) enum PasswordCheck {
+    Valid,
+    ValidLegacy,
+    Invalid,
+}
+
+pub(crate) fn verify_password(password: &str, stored: &str) -> PasswordCheck {
+    match PasswordHash::new(stored) {
+        Ok(hash) => match Argon2::default().verify_password(password.as_bytes(), &hash) {
+            Ok(()) => PasswordCheck::Valid,
+            Err(_) => PasswordCheck::Invalid,
+        },
+        Err(_) if constant_time_eq(password.as_bytes(), stored.as_bytes()) => PasswordCheck::ValidLegacy,
+        Err(_) => PasswordCheck::Invalid,
+    }
+}
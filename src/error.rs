@@ -0,0 +1,117 @@
+use std::fmt;
+use std::io;
+
+use crate::model::Status;
+
+/// Errors returned by [`TodoApp`](crate::TodoApp) operations.
+#[derive(Debug)]
+pub enum TodoError {
+    /// The operation requires a logged-in user.
+    NotLoggedIn,
+    /// No task exists with the given ID.
+    TaskNotFound,
+    /// The current user lacks the access or workspace role this needs.
+    Unauthorized,
+    /// The current user has no project with the given name.
+    ProjectNotFound,
+    /// The current user already has a project with the given name.
+    DuplicateProject,
+    /// No user is registered with the given username.
+    UserNotFound,
+    /// No workspace exists with the given name.
+    WorkspaceNotFound,
+    /// A workspace with the given name already exists.
+    DuplicateWorkspace,
+    /// A user with the requested username is already registered.
+    DuplicateUser,
+    /// The username or password did not match.
+    InvalidCredentials,
+    /// An administrator has locked the account.
+    AccountLocked,
+    /// The task can't be completed while it has open subtasks.
+    OpenSubtasks,
+    /// The task can't be completed while tasks it depends on are open.
+    Blocked,
+    /// The dependency would make a task (indirectly) wait on itself.
+    DependencyCycle,
+    /// The workflow doesn't allow this status change.
+    InvalidTransition(Status, Status),
+    /// The task was changed by another session since it was last loaded.
+    Conflict,
+    /// The input was rejected before anything was changed.
+    Validation(&'static str),
+    /// Hashing or verifying a password failed.
+    PasswordHash(argon2::password_hash::Error),
+    /// Reading or writing the data files failed.
+    Io(io::Error),
+    /// The data files could not be (de)serialized.
+    Serde(serde_json::Error),
+    /// The SQLite backend reported an error.
+    Sqlite(rusqlite::Error),
+}
+
+impl fmt::Display for TodoError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            TodoError::NotLoggedIn => write!(f, "Not logged in"),
+            TodoError::TaskNotFound => write!(f, "Task not found"),
+            TodoError::Unauthorized => write!(f, "Not authorized to do this"),
+            TodoError::ProjectNotFound => write!(f, "Project not found"),
+            TodoError::DuplicateProject => write!(f, "Project already exists"),
+            TodoError::UserNotFound => write!(f, "User not found"),
+            TodoError::WorkspaceNotFound => write!(f, "Workspace not found"),
+            TodoError::DuplicateWorkspace => write!(f, "Workspace already exists"),
+            TodoError::DuplicateUser => write!(f, "Username already exists"),
+            TodoError::InvalidCredentials => write!(f, "Invalid username or password"),
+            TodoError::AccountLocked => write!(f, "Account is locked"),
+            TodoError::OpenSubtasks => write!(f, "Task has open subtasks"),
+            TodoError::Blocked => write!(f, "Task is blocked by open tasks"),
+            TodoError::DependencyCycle => write!(f, "Dependency would create a cycle"),
+            TodoError::InvalidTransition(from, to) => write!(f, "Can't change status from {} to {}", from, to),
+            TodoError::Conflict => write!(f, "Task was modified by another session; reloaded, please try again"),
+            TodoError::Validation(msg) => write!(f, "{}", msg),
+            TodoError::PasswordHash(e) => write!(f, "Password hashing failed: {}", e),
+            TodoError::Io(e) => write!(f, "Storage error: {}", e),
+            TodoError::Serde(e) => write!(f, "Storage format error: {}", e),
+            TodoError::Sqlite(e) => write!(f, "Database error: {}", e),
+        }
+    }
+}
+
+impl std::error::Error for TodoError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            TodoError::Io(e) => Some(e),
+            TodoError::Serde(e) => Some(e),
+            TodoError::Sqlite(e) => Some(e),
+            _ => None,
+        }
+    }
+}
+
+impl From<io::Error> for TodoError {
+    fn from(e: io::Error) -> Self {
+        TodoError::Io(e)
+    }
+}
+
+impl From<serde_json::Error> for TodoError {
+    fn from(e: serde_json::Error) -> Self {
+        TodoError::Serde(e)
+    }
+}
+
+impl From<rusqlite::Error> for TodoError {
+    fn from(e: rusqlite::Error) -> Self {
+        TodoError::Sqlite(e)
+    }
+}
+
+impl From<argon2::password_hash::Error> for TodoError {
+    fn from(e: argon2::password_hash::Error) -> Self {
+        TodoError::PasswordHash(e)
+    }
+}
+
+/// Result type used throughout the crate.
+pub type Result<T, E = TodoError> = std::result::Result<T, E>;
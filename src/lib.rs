@@ -0,0 +1,20 @@
+//! A small multi-user todo engine with pluggable persistence.
+//!
+//! [`TodoApp`] holds the registered users and their tasks and exposes the
+//! operations a frontend needs; the interactive menu in `main.rs` is one such
+//! frontend.
+
+mod app;
+mod auth;
+mod error;
+mod model;
+mod recurrence;
+mod workflow;
+pub mod storage;
+
+pub use app::{TaskOptions, TodoApp};
+pub use error::{Result, TodoError};
+pub use model::{Assignment, Due, Permission, Priority, Progress, Project, Role, Status, Task, User, Workspace};
+pub use recurrence::Recurrence;
+pub use workflow::Workflow;
+pub use storage::Storage;
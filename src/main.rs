@@ -1,327 +1,579 @@
-use std::fs::{self, File};
-use std::io::{self, Write, Read};
-use std::collections::HashMap;
-use serde::{Serialize, Deserialize};
-use chrono::{DateTime, Utc, serde::ts_seconds};
-
-#[derive(Debug, Serialize, Deserialize)]
-struct Task {
-    id: u32,
-    title: String,
-    description: String,
-    completed: bool,
-    #[serde(with = "ts_seconds")]
-    created_at: DateTime<Utc>,
-    user_id: String,
+mod menu;
+mod output;
+
+use std::fs::File;
+use std::io;
+use std::path::PathBuf;
+use std::process::ExitCode;
+
+use chrono::{Local, TimeDelta};
+use clap::{Parser, Subcommand, ValueEnum};
+use todo::{storage, Due, Permission, Priority, Recurrence, Result, Role, Status, TaskOptions, TodoApp, TodoError};
+
+use output::Format;
+
+/// A multi-user todo list. Runs the interactive menu when no command is given.
+#[derive(Parser)]
+#[command(name = "todo", version)]
+struct Cli {
+    /// Storage backend: json, memory, sqlite or sqlite:<path>
+    #[arg(long, global = true, env = "TODO_STORAGE", default_value = "json")]
+    storage: String,
+
+    /// Username for non-interactive commands
+    #[arg(long, short, global = true, env = "TODO_USER")]
+    user: Option<String>,
+
+    /// Password for non-interactive commands
+    #[arg(long, global = true, env = "TODO_PASSWORD", hide_env_values = true)]
+    password: Option<String>,
+
+    /// Days to keep deleted tasks in the trash; 0 keeps them until purged
+    #[arg(long, global = true, env = "TODO_TRASH_DAYS", default_value_t = 30)]
+    trash_days: u32,
+
+    /// Archive tasks this many days after they are closed; 0 turns it off
+    #[arg(long, global = true, env = "TODO_ARCHIVE_DAYS", default_value_t = 0)]
+    archive_days: u32,
+
+    #[command(subcommand)]
+    command: Option<Command>,
 }
 
-#[derive(Debug, Serialize, Deserialize)]
-struct User {
-    username: String,
-    password: String,
+#[derive(Subcommand)]
+enum Command {
+    /// Register a new user with --user and --password
+    Register,
+    /// Add a task and print its ID
+    Add {
+        #[arg(long, short)]
+        title: String,
+        #[arg(long, short, default_value = "")]
+        description: String,
+        #[arg(long, short, default_value = "normal")]
+        priority: Priority,
+        /// Due date: YYYY-MM-DD (all day) or YYYY-MM-DD HH:MM
+        #[arg(long)]
+        due: Option<Due>,
+        /// Tag to attach; may be repeated
+        #[arg(long = "tag")]
+        tags: Vec<String>,
+        /// Project to put the task in
+        #[arg(long)]
+        project: Option<String>,
+        /// Make the new task a subtask of this task
+        #[arg(long)]
+        parent: Option<u32>,
+        /// Repeat rule, e.g. "weekly on mon,thu" or "RRULE:FREQ=DAILY;INTERVAL=2"
+        #[arg(long)]
+        repeat: Option<Recurrence>,
+        /// Put the new task in this workspace
+        #[arg(long)]
+        workspace: Option<String>,
+    },
+    /// List your tasks
+    List {
+        #[arg(long, short, value_enum, default_value = "table")]
+        format: Format,
+        /// Only show open tasks that are overdue, due today or due this week
+        #[arg(long, value_enum)]
+        due: Option<DueFilter>,
+        /// Only show tasks with this tag; may be repeated
+        #[arg(long = "tag")]
+        tags: Vec<String>,
+        /// Hide tasks with this tag; may be repeated
+        #[arg(long = "not-tag")]
+        not_tags: Vec<String>,
+        /// Only show tasks in this project or its subprojects
+        #[arg(long)]
+        project: Option<String>,
+    },
+    /// Show open tasks with a due date, grouped by day
+    Agenda,
+    /// Mark a task as done
+    Done { id: u32 },
+    /// Mark a task as in progress
+    Start { id: u32 },
+    /// Cancel a task
+    Cancel { id: u32 },
+    /// Move a done or cancelled task back to todo
+    Reopen { id: u32 },
+    /// Set a task's status: todo, in_progress, blocked, done or cancelled
+    Status { id: u32, status: Status },
+    /// Change a task's title, description, priority or due date
+    Edit {
+        id: u32,
+        #[arg(long, short)]
+        title: Option<String>,
+        #[arg(long, short)]
+        description: Option<String>,
+        #[arg(long, short)]
+        priority: Option<Priority>,
+        /// New due date
+        #[arg(long, conflicts_with = "no_due")]
+        due: Option<Due>,
+        /// Remove the due date
+        #[arg(long)]
+        no_due: bool,
+    },
+    /// Move a task and its subtasks to the trash
+    Rm { id: u32 },
+    /// Manage deleted tasks
+    #[command(subcommand)]
+    Trash(TrashCommand),
+    /// Manage archived tasks
+    #[command(subcommand)]
+    Archive(ArchiveCommand),
+    /// Add tags to a task
+    Tag {
+        id: u32,
+        #[arg(required = true)]
+        tags: Vec<String>,
+    },
+    /// Remove tags from a task
+    Untag {
+        id: u32,
+        #[arg(required = true)]
+        tags: Vec<String>,
+    },
+    /// List your tags with the number of tasks using each
+    Tags,
+    /// Make a task repeat, or stop it repeating if no rule is given
+    Repeat { id: u32, rule: Option<Recurrence> },
+    /// Mark a task as blocked by another task
+    Block {
+        id: u32,
+        #[arg(long)]
+        by: u32,
+    },
+    /// Remove a blocker from a task
+    Unblock {
+        id: u32,
+        #[arg(long)]
+        by: u32,
+    },
+    /// List open tasks that aren't waiting on anything
+    Next {
+        #[arg(long, short, value_enum, default_value = "table")]
+        format: Format,
+    },
+    /// Make a task a subtask of another, or top-level if no parent is given
+    Nest { id: u32, parent: Option<u32> },
+    /// Move a task into a project, or out of any project if none is given
+    Mv { id: u32, project: Option<String> },
+    /// Give another user viewer or editor access to a task
+    Share {
+        id: u32,
+        username: String,
+        #[arg(long = "as", default_value = "viewer")]
+        permission: Permission,
+    },
+    /// Take back another user's access to a task
+    Unshare { id: u32, username: String },
+    /// Assign a task to a user, or unassign it if no user is given
+    Assign { id: u32, username: Option<String> },
+    /// List tasks assigned to you, or with --by-me those you assigned to others
+    Assigned {
+        #[arg(long)]
+        by_me: bool,
+        #[arg(long, short, value_enum, default_value = "table")]
+        format: Format,
+    },
+    /// Show who a task was assigned to over time
+    History { id: u32 },
+    /// Manage projects
+    #[command(subcommand)]
+    Project(ProjectCommand),
+    /// Manage workspaces and their members
+    #[command(subcommand)]
+    Workspace(WorkspaceCommand),
+    /// Change your password; --password is the current one
+    Passwd {
+        #[arg(long, env = "TODO_NEW_PASSWORD", hide_env_values = true)]
+        new_password: String,
+    },
+    /// Delete your account and all your tasks
+    DeleteAccount {
+        /// Save your tasks to this JSON file first
+        #[arg(long)]
+        export: Option<PathBuf>,
+    },
+    /// Manage user accounts (admins only)
+    #[command(subcommand)]
+    User(UserCommand),
+    /// Copy tasks.json and users.json into the selected storage backend
+    Import,
 }
 
-struct TodoApp {
-    tasks: HashMap<u32, Task>,
-    users: HashMap<String, User>,
-    current_user: Option<String>,
-    next_task_id: u32,
+#[derive(Subcommand)]
+enum ProjectCommand {
+    /// Create a project; use a/b to nest b under a
+    Add { name: String },
+    /// Rename or re-parent a project
+    Rename { name: String, new_name: String },
+    /// Delete a project without subprojects; its tasks are kept
+    Rm { name: String },
+    /// List projects with their completion percentage
+    List,
+    /// Give another user viewer or editor access to every task in a project
+    Share {
+        name: String,
+        username: String,
+        #[arg(long = "as", default_value = "viewer")]
+        permission: Permission,
+    },
+    /// Take back another user's access to a project
+    Unshare { name: String, username: String },
 }
 
-impl TodoApp {
-    fn new() -> Self {
-        Self {
-            tasks: HashMap::new(),
-            users: HashMap::new(),
-            current_user: None,
-            next_task_id: 1,
-        }
-    }
+#[derive(Subcommand)]
+enum WorkspaceCommand {
+    /// Create a workspace owned by you
+    Add { name: String },
+    /// Delete a workspace you own; its tasks become private again
+    Rm { name: String },
+    /// List the workspaces you belong to with your role
+    List,
+    /// List a workspace's members and their roles
+    Members { name: String },
+    /// Add a member or change their role: guest, member, admin or owner
+    Invite { name: String, username: String, role: Role },
+    /// Remove a member, or yourself
+    Remove { name: String, username: String },
+    /// List the tasks in a workspace
+    Tasks {
+        name: String,
+        #[arg(long, short, value_enum, default_value = "table")]
+        format: Format,
+    },
+    /// Move a task into a workspace, or out of any workspace if none is given
+    Mv { id: u32, workspace: Option<String> },
+    /// Move a project into a workspace, or out of any workspace if none is given
+    Project { project: String, workspace: Option<String> },
+}
 
-    fn register(&mut self, username: String, password: String) -> Result<(), &'static str> {
-        if self.users.contains_key(&username) {
-            return Err("Username already exists");
-        }
+#[derive(Subcommand)]
+enum UserCommand {
+    /// List all users
+    List,
+    /// Set a new password for a user
+    ResetPassword {
+        username: String,
+        #[arg(long, env = "TODO_NEW_PASSWORD", hide_env_values = true)]
+        new_password: String,
+    },
+    /// Stop a user from logging in
+    Lock { username: String },
+    /// Let a locked user log in again
+    Unlock { username: String },
+    /// Make a user an admin
+    Promote { username: String },
+    /// Take away a user's admin role
+    Demote { username: String },
+    /// Delete a user, giving their tasks to another user or deleting them
+    Rm {
+        username: String,
+        /// User who takes over the deleted user's tasks and projects
+        #[arg(long, required_unless_present = "cascade")]
+        reassign_to: Option<String>,
+        /// Delete the user's tasks and projects too
+        #[arg(long, conflicts_with = "reassign_to")]
+        cascade: bool,
+    },
+}
 
-        self.users.insert(username.clone(), User {
-            username,
-            password,
-        });
-        self.save_users().unwrap();
-        Ok(())
-    }
+#[derive(Subcommand)]
+enum TrashCommand {
+    /// List deleted tasks, most recent first
+    List {
+        #[arg(long, short, value_enum, default_value = "table")]
+        format: Format,
+    },
+    /// Bring a deleted task and its subtasks back
+    Restore { id: u32 },
+    /// Permanently delete one task from the trash, or all of it
+    Purge { id: Option<u32> },
+}
 
-    fn login(&mut self, username: String, password: String) -> Result<(), &'static str> {
-        match self.users.get(&username) {
-            Some(user) if user.password == password => {
-                self.current_user = Some(username);
-                Ok(())
-            }
-            _ => Err("Invalid username or password"),
-        }
-    }
+#[derive(Subcommand)]
+enum ArchiveCommand {
+    /// Archive a done or cancelled task and its subtasks
+    Add { id: u32 },
+    /// Archive every task closed more than this many days ago
+    Old { days: u32 },
+    /// Search archived tasks by title, description or tag
+    Search {
+        #[arg(default_value = "")]
+        query: String,
+        #[arg(long, short, value_enum, default_value = "table")]
+        format: Format,
+    },
+    /// Move an archived task back into the task list
+    Restore { id: u32 },
+}
 
-    fn add_task(&mut self, title: String, description: String) -> Result<(), &'static str> {
-        let user_id = self.current_user.clone().ok_or("Not logged in")?;
+#[derive(Clone, Copy, ValueEnum)]
+enum DueFilter {
+    Overdue,
+    Today,
+    Week,
+}
 
-        let task = Task {
-            id: self.next_task_id,
-            title,
-            description,
-            completed: false,
-            created_at: Utc::now(),
-            user_id,
-        };
+fn open_app(cli: &Cli) -> Result<TodoApp> {
+    let mut app = TodoApp::with_storage(storage::open(&cli.storage)?);
+    app.set_trash_retention(match cli.trash_days {
+        0 => None,
+        days => Some(TimeDelta::days(days.into())),
+    });
+    app.set_auto_archive(match cli.archive_days {
+        0 => None,
+        days => Some(TimeDelta::days(days.into())),
+    });
+    app.load_tasks()?;
+    app.load_users()?;
+    Ok(app)
+}
 
-        self.tasks.insert(self.next_task_id, task);
-        self.next_task_id += 1;
-        self.save_tasks().unwrap();
-        Ok(())
+fn credentials(cli: &Cli) -> Result<(String, String)> {
+    match (&cli.user, &cli.password) {
+        (Some(user), Some(password)) => Ok((user.clone(), password.clone())),
+        _ => Err(TodoError::Validation("Set --user and --password (or TODO_USER and TODO_PASSWORD)")),
     }
+}
 
-    fn complete_task(&mut self, task_id: u32) -> Result<(), &'static str> {
-        let user_id = self.current_user.clone().ok_or("Not logged in")?;
-
-        let task = self.tasks.get_mut(&task_id).ok_or("Task not found")?;
-        if task.user_id != user_id {
-            return Err("Not authorized to modify this task");
-        }
-
-        task.completed = true;
-        self.save_tasks().unwrap();
-        Ok(())
+fn run(cli: &Cli, command: &Command) -> Result<()> {
+    if let Command::Import = command {
+        let mut target = storage::open(&cli.storage)?;
+        let (users, tasks) = storage::import(&mut storage::JsonStorage::default(), target.as_mut())?;
+        println!("Imported {} users and {} tasks", users, tasks);
+        return Ok(());
     }
 
-    fn edit_task(&mut self, task_id: u32, title: String, description: String) -> Result<(), &'static str> {
-        let user_id = self.current_user.clone().ok_or("Not logged in")?;
-
-        let task = self.tasks.get_mut(&task_id).ok_or("Task not found")?;
-        if task.user_id != user_id {
-            return Err("Not authorized to modify this task");
-        }
+    let mut app = open_app(cli)?;
+    let (user, password) = credentials(cli)?;
 
-        task.title = title;
-        task.description = description;
-        self.save_tasks().unwrap();
-        Ok(())
+    if let Command::Register = command {
+        app.register(user, password)?;
+        println!("Registration successful!");
+        return Ok(());
     }
 
-    fn delete_task(&mut self, task_id: u32) -> Result<(), &'static str> {
-        let user_id = self.current_user.clone().ok_or("Not logged in")?;
-
-        let task = self.tasks.get(&task_id).ok_or("Task not found")?;
-        if task.user_id != user_id {
-            return Err("Not authorized to delete this task");
+    app.login(user, password)?;
+
+    match command {
+        Command::Add { title, description, priority, due, tags, project, parent, repeat, workspace } => {
+            let options = TaskOptions {
+                tags: tags.clone(),
+                project: project.clone(),
+                parent: *parent,
+                recurrence: repeat.clone(),
+                workspace: workspace.clone(),
+            };
+            let id = app.create_task(title.clone(), description.clone(), *priority, *due, options)?;
+            println!("{}", id);
         }
-
-        self.tasks.remove(&task_id);
-        self.save_tasks().unwrap();
-        Ok(())
-    }
-
-    fn list_tasks(&self) -> Result<Vec<&Task>, &'static str> {
-        let user_id = self.current_user.as_ref().ok_or("Not logged in")?;
-
-        Ok(self.tasks.values()
-            .filter(|task| task.user_id == *user_id)
-            .collect())
-    }
-
-    fn save_tasks(&self) -> io::Result<()> {
-        let json = serde_json::to_string(&self.tasks)?;
-        fs::write("tasks.json", json)?;
-        Ok(())
-    }
-
-    fn load_tasks(&mut self) -> io::Result<()> {
-        match fs::read_to_string("tasks.json") {
-            Ok(contents) => {
-                self.tasks = serde_json::from_str(&contents)?;
-                self.next_task_id = self.tasks.keys().max().map_or(1, |max| max + 1);
-                Ok(())
+        Command::List { format, due, tags, not_tags, project } => {
+            let mut tasks = match due {
+                None => app.list_tasks_tagged(tags.iter().map(String::as_str), not_tags.iter().map(String::as_str))?,
+                Some(DueFilter::Overdue) => app.overdue_tasks()?,
+                Some(DueFilter::Today) => app.tasks_due_today()?,
+                Some(DueFilter::Week) => app.tasks_due_this_week()?,
+            };
+            if due.is_some() && (!tags.is_empty() || !not_tags.is_empty()) {
+                let tagged = app.list_tasks_tagged(tags.iter().map(String::as_str), not_tags.iter().map(String::as_str))?;
+                tasks.retain(|task| tagged.iter().any(|t| t.id == task.id));
+            }
+            if let Some(project) = project {
+                let in_project = app.project_tasks(project)?;
+                tasks.retain(|task| in_project.iter().any(|t| t.id == task.id));
             }
-            Err(e) if e.kind() == io::ErrorKind::NotFound => Ok(()),
-            Err(e) => Err(e),
+            output::write_tasks(&mut io::stdout().lock(), &tasks, *format, app.current_user().unwrap_or_default(), |task| app.is_blocked(task))?;
         }
-    }
-
-    fn save_users(&self) -> io::Result<()> {
-        let json = serde_json::to_string(&self.users)?;
-        fs::write("users.json", json)?;
-        Ok(())
-    }
-
-    fn load_users(&mut self) -> io::Result<()> {
-        match fs::read_to_string("users.json") {
-            Ok(contents) => {
-                self.users = serde_json::from_str(&contents)?;
-                Ok(())
+        Command::Block { id, by } => app.add_dependency(*id, *by)?,
+        Command::Unblock { id, by } => app.remove_dependency(*id, *by)?,
+        Command::Next { format } => {
+            let tasks = app.next_actionable_tasks()?;
+            output::write_tasks(&mut io::stdout().lock(), &tasks, *format, app.current_user().unwrap_or_default(), |_| false)?;
+        }
+        Command::Agenda => output::write_agenda(&mut io::stdout().lock(), &app.agenda()?)?,
+        Command::Done { id } => {
+            if let Some(next) = app.complete_task(*id)? {
+                let due = app.get_task(next)?.due().map(|due| due.to_string()).unwrap_or_default();
+                println!("Next occurrence: {} due {}", next, due);
             }
-            Err(e) if e.kind() == io::ErrorKind::NotFound => Ok(()),
-            Err(e) => Err(e),
         }
-    }
-}
-
-fn main() {
-    let mut app = TodoApp::new();
-    app.load_tasks().unwrap();
-    app.load_users().unwrap();
-
-    loop {
-        if app.current_user.is_none() {
-            println!("\nWelcome to Todo App!");
-            println!("1. Login");
-            println!("2. Register");
-            println!("3. Exit");
-
-            let mut choice = String::new();
-            io::stdin().read_line(&mut choice).unwrap();
-
-            match choice.trim() {
-                "1" => {
-                    print!("Username: ");
-                    io::stdout().flush().unwrap();
-                    let mut username = String::new();
-                    io::stdin().read_line(&mut username).unwrap();
-
-                    print!("Password: ");
-                    io::stdout().flush().unwrap();
-                    let mut password = String::new();
-                    io::stdin().read_line(&mut password).unwrap();
-
-                    match app.login(username.trim().to_string(), password.trim().to_string()) {
-                        Ok(_) => println!("Login successful!"),
-                        Err(e) => println!("Error: {}", e),
-                    }
-                }
-                "2" => {
-                    print!("Username: ");
-                    io::stdout().flush().unwrap();
-                    let mut username = String::new();
-                    io::stdin().read_line(&mut username).unwrap();
-
-                    print!("Password: ");
-                    io::stdout().flush().unwrap();
-                    let mut password = String::new();
-                    io::stdin().read_line(&mut password).unwrap();
-
-                    match app.register(username.trim().to_string(), password.trim().to_string()) {
-                        Ok(_) => println!("Registration successful!"),
-                        Err(e) => println!("Error: {}", e),
-                    }
-                }
-                "3" => break,
-                _ => println!("Invalid choice"),
+        Command::Start { id } => {
+            app.set_status(*id, Status::InProgress)?;
+        }
+        Command::Cancel { id } => {
+            app.set_status(*id, Status::Cancelled)?;
+        }
+        Command::Reopen { id } => app.reopen_task(*id)?,
+        Command::Status { id, status } => {
+            app.set_status(*id, *status)?;
+        }
+        Command::Repeat { id, rule } => app.set_recurrence(*id, rule.clone())?,
+        Command::Edit { id, title, description, priority, due, no_due } => {
+            let task = app.get_task(*id)?;
+            let title = title.clone().unwrap_or_else(|| task.title.clone());
+            let description = description.clone().unwrap_or_else(|| task.description.clone());
+            let priority = priority.unwrap_or(task.priority);
+            let due = if *no_due { None } else { due.or(task.due()) };
+            app.edit_task(*id, title, description, priority, due)?;
+        }
+        Command::Rm { id } => app.delete_task(*id)?,
+        Command::Trash(TrashCommand::List { format }) => {
+            let tasks = app.list_trash()?;
+            output::write_tasks(&mut io::stdout().lock(), &tasks, *format, app.current_user().unwrap_or_default(), |_| false)?;
+        }
+        Command::Trash(TrashCommand::Restore { id }) => app.restore_task(*id)?,
+        Command::Trash(TrashCommand::Purge { id }) => {
+            let purged = app.purge_trash(*id)?;
+            println!("Purged {} tasks", purged);
+        }
+        Command::Archive(ArchiveCommand::Add { id }) => app.archive_task(*id)?,
+        Command::Archive(ArchiveCommand::Old { days }) => {
+            let archived = app.archive_closed_before(TimeDelta::days((*days).into()))?;
+            println!("Archived {} tasks", archived);
+        }
+        Command::Archive(ArchiveCommand::Search { query, format }) => {
+            let tasks = app.search_archive(query)?;
+            output::write_tasks(&mut io::stdout().lock(), &tasks, *format, app.current_user().unwrap_or_default(), |_| false)?;
+        }
+        Command::Archive(ArchiveCommand::Restore { id }) => app.unarchive_task(*id)?,
+        Command::Tag { id, tags } => app.tag_task(*id, tags.iter().map(String::as_str))?,
+        Command::Untag { id, tags } => app.untag_task(*id, tags.iter().map(String::as_str))?,
+        Command::Tags => {
+            for (tag, count) in app.tag_counts()? {
+                println!("{}\t{}", tag, count);
             }
-        } else {
-            println!("\nTodo App Menu:");
-            println!("1. Add Task");
-            println!("2. List Tasks");
-            println!("3. Complete Task");
-            println!("4. Edit Task");
-            println!("5. Delete Task");
-            println!("6. Logout");
-
-            let mut choice = String::new();
-            io::stdin().read_line(&mut choice).unwrap();
-
-            match choice.trim() {
-                "1" => {
-                    print!("Title: ");
-                    io::stdout().flush().unwrap();
-                    let mut title = String::new();
-                    io::stdin().read_line(&mut title).unwrap();
-
-                    print!("Description: ");
-                    io::stdout().flush().unwrap();
-                    let mut description = String::new();
-                    io::stdin().read_line(&mut description).unwrap();
-
-                    match app.add_task(title.trim().to_string(), description.trim().to_string()) {
-                        Ok(_) => println!("Task added successfully!"),
-                        Err(e) => println!("Error: {}", e),
-                    }
-                }
-                "2" => {
-                    match app.list_tasks() {
-                        Ok(tasks) => {
-                            for task in tasks {
-                                println!("\nID: {}", task.id);
-                                println!("Title: {}", task.title);
-                                println!("Description: {}", task.description);
-                                println!("Status: {}", if task.completed { "Completed" } else { "Pending" });
-                                println!("Created: {}", task.created_at);
-                            }
-                        }
-                        Err(e) => println!("Error: {}", e),
-                    }
+        }
+        Command::Nest { id, parent } => app.set_parent(*id, *parent)?,
+        Command::Mv { id, project } => app.move_task(*id, project.as_deref())?,
+        Command::Share { id, username, permission } => app.share_task(*id, username, Some(*permission))?,
+        Command::Unshare { id, username } => app.share_task(*id, username, None)?,
+        Command::Assign { id, username } => app.assign_task(*id, username.as_deref())?,
+        Command::Assigned { by_me, format } => {
+            let tasks = if *by_me { app.assigned_by_me()? } else { app.assigned_to_me()? };
+            output::write_tasks(&mut io::stdout().lock(), &tasks, *format, app.current_user().unwrap_or_default(), |task| app.is_blocked(task))?;
+        }
+        Command::History { id } => {
+            for assignment in &app.get_task(*id)?.assignments {
+                let from = assignment.from.as_deref().unwrap_or("-");
+                let to = assignment.to.as_deref().unwrap_or("-");
+                println!("{}\t{} -> {}\tby {}", assignment.at.with_timezone(&Local).format("%Y-%m-%d %H:%M"), from, to, assignment.by);
+            }
+        }
+        Command::Project(ProjectCommand::Share { name, username, permission }) => {
+            app.share_project(name, username, Some(*permission))?
+        }
+        Command::Project(ProjectCommand::Unshare { name, username }) => app.share_project(name, username, None)?,
+        Command::Project(ProjectCommand::Add { name }) => app.create_project(name)?,
+        Command::Project(ProjectCommand::Rename { name, new_name }) => app.rename_project(name, new_name)?,
+        Command::Project(ProjectCommand::Rm { name }) => app.delete_project(name)?,
+        Command::Project(ProjectCommand::List) => {
+            let user = app.current_user().unwrap_or_default();
+            for project in app.list_projects()? {
+                let progress = app.project_progress(&project.name)?;
+                let owner = if project.user_id == user { String::new() } else { format!(" (from {})", project.user_id) };
+                println!("{}{}\t{}/{}\t{:.0}%", project.name, owner, progress.done, progress.total, progress.percent());
+            }
+        }
+        Command::Workspace(WorkspaceCommand::Add { name }) => app.create_workspace(name)?,
+        Command::Workspace(WorkspaceCommand::Rm { name }) => app.delete_workspace(name)?,
+        Command::Workspace(WorkspaceCommand::List) => {
+            let user = app.current_user().unwrap_or_default();
+            for workspace in app.list_workspaces()? {
+                println!("{}\t{}", workspace.name, workspace.members[user]);
+            }
+        }
+        Command::Workspace(WorkspaceCommand::Members { name }) => {
+            let workspace = app.list_workspaces()?
+                .into_iter()
+                .find(|workspace| workspace.name == *name)
+                .ok_or(TodoError::WorkspaceNotFound)?;
+            for (member, role) in &workspace.members {
+                println!("{}\t{}", member, role);
+            }
+        }
+        Command::Workspace(WorkspaceCommand::Invite { name, username, role }) => app.set_member(name, username, *role)?,
+        Command::Workspace(WorkspaceCommand::Remove { name, username }) => app.remove_member(name, username)?,
+        Command::Workspace(WorkspaceCommand::Tasks { name, format }) => {
+            let tasks = app.workspace_tasks(name)?;
+            output::write_tasks(&mut io::stdout().lock(), &tasks, *format, app.current_user().unwrap_or_default(), |task| app.is_blocked(task))?;
+        }
+        Command::Workspace(WorkspaceCommand::Mv { id, workspace }) => app.move_task_to_workspace(*id, workspace.as_deref())?,
+        Command::Workspace(WorkspaceCommand::Project { project, workspace }) => {
+            app.move_project_to_workspace(project, workspace.as_deref())?
+        }
+        Command::Passwd { new_password } => {
+            let (_, password) = credentials(cli)?;
+            app.change_password(&password, new_password)?;
+            println!("Password changed");
+        }
+        Command::DeleteAccount { export } => {
+            let (_, password) = credentials(cli)?;
+            // Write the export out in full before anything is deleted.
+            if let Some(path) = export {
+                let mut file = File::create(path)?;
+                output::write_tasks(&mut file, &app.account_tasks()?, Format::Json, "", |_| false)?;
+                file.sync_all()?;
+            }
+            app.delete_account(&password)?;
+            println!("Account deleted");
+        }
+        Command::User(UserCommand::List) => {
+            for user in app.list_users()? {
+                let mut flags = Vec::new();
+                if user.admin {
+                    flags.push("admin");
                 }
-                "3" => {
-                    print!("Task ID: ");
-                    io::stdout().flush().unwrap();
-                    let mut id = String::new();
-                    io::stdin().read_line(&mut id).unwrap();
-
-                    match id.trim().parse() {
-                        Ok(task_id) => {
-                            match app.complete_task(task_id) {
-                                Ok(_) => println!("Task marked as completed!"),
-                                Err(e) => println!("Error: {}", e),
-                            }
-                        }
-                        Err(_) => println!("Invalid task ID"),
-                    }
+                if user.locked {
+                    flags.push("locked");
                 }
-                "4" => {
-                    print!("Task ID: ");
-                    io::stdout().flush().unwrap();
-                    let mut id = String::new();
-                    io::stdin().read_line(&mut id).unwrap();
+                println!("{}\t{}", user.username, flags.join(","));
+            }
+        }
+        Command::User(UserCommand::ResetPassword { username, new_password }) => app.reset_password(username, new_password)?,
+        Command::User(UserCommand::Lock { username }) => app.set_locked(username, true)?,
+        Command::User(UserCommand::Unlock { username }) => app.set_locked(username, false)?,
+        Command::User(UserCommand::Promote { username }) => app.set_admin(username, true)?,
+        Command::User(UserCommand::Demote { username }) => app.set_admin(username, false)?,
+        Command::User(UserCommand::Rm { username, reassign_to, .. }) => app.delete_user(username, reassign_to.as_deref())?,
+        Command::Register | Command::Import => unreachable!(),
+    }
+    Ok(())
+}
 
-                    print!("New Title: ");
-                    io::stdout().flush().unwrap();
-                    let mut title = String::new();
-                    io::stdin().read_line(&mut title).unwrap();
+fn exit_code(e: &TodoError) -> u8 {
+    match e {
+        TodoError::Validation(_) | TodoError::DependencyCycle | TodoError::InvalidTransition(..) => 2,
+        TodoError::NotLoggedIn | TodoError::InvalidCredentials | TodoError::AccountLocked => 3,
+        TodoError::TaskNotFound | TodoError::ProjectNotFound | TodoError::UserNotFound | TodoError::WorkspaceNotFound => 4,
+        TodoError::Unauthorized => 5,
+        TodoError::Conflict
+        | TodoError::OpenSubtasks
+        | TodoError::Blocked
+        | TodoError::DuplicateUser
+        | TodoError::DuplicateProject
+        | TodoError::DuplicateWorkspace => 6,
+        _ => 1,
+    }
+}
 
-                    print!("New Description: ");
-                    io::stdout().flush().unwrap();
-                    let mut description = String::new();
-                    io::stdin().read_line(&mut description).unwrap();
+fn main() -> ExitCode {
+    let cli = Cli::parse();
 
-                    match id.trim().parse() {
-                        Ok(task_id) => {
-                            match app.edit_task(task_id, title.trim().to_string(), description.trim().to_string()) {
-                                Ok(_) => println!("Task updated successfully!"),
-                                Err(e) => println!("Error: {}", e),
-                            }
-                        }
-                        Err(_) => println!("Invalid task ID"),
-                    }
-                }
-                "5" => {
-                    print!("Task ID: ");
-                    io::stdout().flush().unwrap();
-                    let mut id = String::new();
-                    io::stdin().read_line(&mut id).unwrap();
+    let result = match &cli.command {
+        Some(command) => run(&cli, command),
+        None => open_app(&cli).map(|mut app| menu::run(&mut app)),
+    };
 
-                    match id.trim().parse() {
-                        Ok(task_id) => {
-                            match app.delete_task(task_id) {
-                                Ok(_) => println!("Task deleted successfully!"),
-                                Err(e) => println!("Error: {}", e),
-                            }
-                        }
-                        Err(_) => println!("Invalid task ID"),
-                    }
-                }
-                "6" => {
-                    app.current_user = None;
-                    println!("Logged out successfully!");
-                }
-                _ => println!("Invalid choice"),
-            }
+    match result {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(e) => {
+            eprintln!("Error: {}", e);
+            ExitCode::from(exit_code(&e))
         }
     }
 }
-
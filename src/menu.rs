@@ -0,0 +1,287 @@
+use std::io::{self, Write};
+
+use todo::{Due, Priority, TodoApp};
+
+use crate::output;
+
+/// Parses a priority typed at the menu; an empty answer picks `default`.
+fn parse_priority(input: &str, default: Priority) -> Result<Priority, &'static str> {
+    if input.is_empty() {
+        Ok(default)
+    } else {
+        input.parse()
+    }
+}
+
+/// Parses a due date typed at the menu; an empty answer keeps `current` and
+/// `none` clears it.
+fn parse_due(input: &str, current: Option<Due>) -> Result<Option<Due>, &'static str> {
+    match input {
+        "" => Ok(current),
+        "none" => Ok(None),
+        _ => input.parse().map(Some),
+    }
+}
+
+/// Runs the interactive numbered menu until the user exits.
+pub fn run(app: &mut TodoApp) {
+    loop {
+        if app.current_user().is_none() {
+            println!("\nWelcome to Todo App!");
+            println!("1. Login");
+            println!("2. Register");
+            println!("3. Exit");
+
+            let mut choice = String::new();
+            io::stdin().read_line(&mut choice).unwrap();
+
+            match choice.trim() {
+                "1" => {
+                    print!("Username: ");
+                    io::stdout().flush().unwrap();
+                    let mut username = String::new();
+                    io::stdin().read_line(&mut username).unwrap();
+
+                    print!("Password: ");
+                    io::stdout().flush().unwrap();
+                    let mut password = String::new();
+                    io::stdin().read_line(&mut password).unwrap();
+
+                    match app.login(username.trim().to_string(), password.trim().to_string()) {
+                        Ok(_) => println!("Login successful!"),
+                        Err(e) => println!("Error: {}", e),
+                    }
+                }
+                "2" => {
+                    print!("Username: ");
+                    io::stdout().flush().unwrap();
+                    let mut username = String::new();
+                    io::stdin().read_line(&mut username).unwrap();
+
+                    print!("Password: ");
+                    io::stdout().flush().unwrap();
+                    let mut password = String::new();
+                    io::stdin().read_line(&mut password).unwrap();
+
+                    match app.register(username.trim().to_string(), password.trim().to_string()) {
+                        Ok(_) => println!("Registration successful!"),
+                        Err(e) => println!("Error: {}", e),
+                    }
+                }
+                "3" => break,
+                _ => println!("Invalid choice"),
+            }
+        } else {
+            println!("\nTodo App Menu:");
+            println!("1. Add Task");
+            println!("2. List Tasks");
+            println!("3. Complete Task");
+            println!("4. Edit Task");
+            println!("5. Delete Task");
+            println!("6. Logout");
+            println!("7. Agenda");
+            println!("8. Reopen Task");
+            println!("9. Change Password");
+
+            let mut choice = String::new();
+            io::stdin().read_line(&mut choice).unwrap();
+
+            match choice.trim() {
+                "1" => {
+                    print!("Title: ");
+                    io::stdout().flush().unwrap();
+                    let mut title = String::new();
+                    io::stdin().read_line(&mut title).unwrap();
+
+                    print!("Description: ");
+                    io::stdout().flush().unwrap();
+                    let mut description = String::new();
+                    io::stdin().read_line(&mut description).unwrap();
+
+                    print!("Priority (low/normal/high/urgent) [normal]: ");
+                    io::stdout().flush().unwrap();
+                    let mut priority = String::new();
+                    io::stdin().read_line(&mut priority).unwrap();
+
+                    print!("Due (YYYY-MM-DD [HH:MM]) [none]: ");
+                    io::stdout().flush().unwrap();
+                    let mut due = String::new();
+                    io::stdin().read_line(&mut due).unwrap();
+
+                    let parsed = parse_priority(priority.trim(), Priority::Normal)
+                        .and_then(|priority| Ok((priority, parse_due(due.trim(), None)?)));
+                    match parsed {
+                        Ok((priority, due)) => {
+                            match app.add_task(title.trim().to_string(), description.trim().to_string(), priority, due) {
+                                Ok(_) => println!("Task added successfully!"),
+                                Err(e) => println!("Error: {}", e),
+                            }
+                        }
+                        Err(e) => println!("Error: {}", e),
+                    }
+                }
+                "2" => {
+                    match app.load_tasks().and_then(|_| app.list_tasks()) {
+                        Ok(tasks) => {
+                            for task in tasks {
+                                println!("\nID: {}", task.id);
+                                println!("Title: {}", task.title);
+                                println!("Description: {}", task.description);
+                                println!("Status: {}", task.status);
+                                if Some(task.user_id.as_str()) != app.current_user() {
+                                    println!("Owner: {}", task.user_id);
+                                }
+                                if let Some(assignee) = &task.assignee {
+                                    println!("Assigned to: {}", assignee);
+                                }
+                                println!("Priority: {}", task.priority);
+                                if let Some(due) = task.due() {
+                                    println!("Due: {}", due);
+                                }
+                                if let Some(recurrence) = &task.recurrence {
+                                    println!("Repeats: {}", recurrence);
+                                }
+                                let blockers = app.open_blockers(task);
+                                if !blockers.is_empty() {
+                                    let ids: Vec<String> = blockers.iter().map(u32::to_string).collect();
+                                    println!("Blocked by: {}", ids.join(", "));
+                                }
+                                if let Some(parent_id) = task.parent_id {
+                                    println!("Subtask of: {}", parent_id);
+                                }
+                                if let Some(project) = &task.project {
+                                    println!("Project: {}", project);
+                                }
+                                if !task.tags.is_empty() {
+                                    println!("Tags: {}", task.tags.iter().cloned().collect::<Vec<_>>().join(", "));
+                                }
+                                println!("Created: {}", task.created_at);
+                            }
+                        }
+                        Err(e) => println!("Error: {}", e),
+                    }
+                }
+                "3" => {
+                    print!("Task ID: ");
+                    io::stdout().flush().unwrap();
+                    let mut id = String::new();
+                    io::stdin().read_line(&mut id).unwrap();
+
+                    match id.trim().parse() {
+                        Ok(task_id) => {
+                            match app.complete_task(task_id) {
+                                Ok(Some(next)) => println!("Task marked as completed! Next occurrence is task {}.", next),
+                                Ok(None) => println!("Task marked as completed!"),
+                                Err(e) => println!("Error: {}", e),
+                            }
+                        }
+                        Err(_) => println!("Invalid task ID"),
+                    }
+                }
+                "4" => {
+                    print!("Task ID: ");
+                    io::stdout().flush().unwrap();
+                    let mut id = String::new();
+                    io::stdin().read_line(&mut id).unwrap();
+
+                    print!("New Title: ");
+                    io::stdout().flush().unwrap();
+                    let mut title = String::new();
+                    io::stdin().read_line(&mut title).unwrap();
+
+                    print!("New Description: ");
+                    io::stdout().flush().unwrap();
+                    let mut description = String::new();
+                    io::stdin().read_line(&mut description).unwrap();
+
+                    print!("New Priority (low/normal/high/urgent) [unchanged]: ");
+                    io::stdout().flush().unwrap();
+                    let mut priority = String::new();
+                    io::stdin().read_line(&mut priority).unwrap();
+
+                    print!("New Due (YYYY-MM-DD [HH:MM], none) [unchanged]: ");
+                    io::stdout().flush().unwrap();
+                    let mut due = String::new();
+                    io::stdin().read_line(&mut due).unwrap();
+
+                    match id.trim().parse() {
+                        Ok(task_id) => {
+                            let current = app.get_task(task_id).ok();
+                            let parsed = parse_priority(priority.trim(), current.map_or(Priority::Normal, |task| task.priority))
+                                .and_then(|priority| Ok((priority, parse_due(due.trim(), current.and_then(|task| task.due()))?)));
+                            match parsed {
+                                Ok((priority, due)) => {
+                                    match app.edit_task(task_id, title.trim().to_string(), description.trim().to_string(), priority, due) {
+                                        Ok(_) => println!("Task updated successfully!"),
+                                        Err(e) => println!("Error: {}", e),
+                                    }
+                                }
+                                Err(e) => println!("Error: {}", e),
+                            }
+                        }
+                        Err(_) => println!("Invalid task ID"),
+                    }
+                }
+                "5" => {
+                    print!("Task ID: ");
+                    io::stdout().flush().unwrap();
+                    let mut id = String::new();
+                    io::stdin().read_line(&mut id).unwrap();
+
+                    match id.trim().parse() {
+                        Ok(task_id) => {
+                            match app.delete_task(task_id) {
+                                Ok(_) => println!("Task moved to trash!"),
+                                Err(e) => println!("Error: {}", e),
+                            }
+                        }
+                        Err(_) => println!("Invalid task ID"),
+                    }
+                }
+                "6" => {
+                    app.logout();
+                    println!("Logged out successfully!");
+                }
+                "7" => {
+                    match app.load_tasks().and_then(|_| app.agenda()) {
+                        Ok(agenda) => output::write_agenda(&mut io::stdout().lock(), &agenda).unwrap(),
+                        Err(e) => println!("Error: {}", e),
+                    }
+                }
+                "8" => {
+                    print!("Task ID: ");
+                    io::stdout().flush().unwrap();
+                    let mut id = String::new();
+                    io::stdin().read_line(&mut id).unwrap();
+
+                    match id.trim().parse() {
+                        Ok(task_id) => {
+                            match app.reopen_task(task_id) {
+                                Ok(_) => println!("Task reopened!"),
+                                Err(e) => println!("Error: {}", e),
+                            }
+                        }
+                        Err(_) => println!("Invalid task ID"),
+                    }
+                }
+                "9" => {
+                    print!("Current Password: ");
+                    io::stdout().flush().unwrap();
+                    let mut old = String::new();
+                    io::stdin().read_line(&mut old).unwrap();
+
+                    print!("New Password: ");
+                    io::stdout().flush().unwrap();
+                    let mut new = String::new();
+                    io::stdin().read_line(&mut new).unwrap();
+
+                    match app.change_password(old.trim(), new.trim()) {
+                        Ok(_) => println!("Password changed successfully!"),
+                        Err(e) => println!("Error: {}", e),
+                    }
+                }
+                _ => println!("Invalid choice"),
+            }
+        }
+    }
+}
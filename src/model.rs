@@ -0,0 +1,444 @@
+use std::collections::{BTreeMap, BTreeSet};
+use std::fmt;
+use std::str::FromStr;
+
+use serde::{Serialize, Deserialize};
+use chrono::{DateTime, Local, NaiveDate, NaiveDateTime, TimeZone, Utc};
+use chrono::serde::{ts_seconds, ts_seconds_option};
+
+use crate::recurrence::Recurrence;
+
+/// How urgent a task is. Ordered from lowest to highest.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Priority {
+    Low,
+    #[default]
+    Normal,
+    High,
+    Urgent,
+}
+
+impl fmt::Display for Priority {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.pad(match self {
+            Priority::Low => "low",
+            Priority::Normal => "normal",
+            Priority::High => "high",
+            Priority::Urgent => "urgent",
+        })
+    }
+}
+
+impl FromStr for Priority {
+    type Err = &'static str;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_ascii_lowercase().as_str() {
+            "low" => Ok(Priority::Low),
+            "normal" => Ok(Priority::Normal),
+            "high" => Ok(Priority::High),
+            "urgent" => Ok(Priority::Urgent),
+            _ => Err("Priority must be one of low, normal, high, urgent"),
+        }
+    }
+}
+
+/// Where a task is in its lifecycle.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Status {
+    #[default]
+    Todo,
+    InProgress,
+    Blocked,
+    Done,
+    Cancelled,
+}
+
+impl Status {
+    /// Done and cancelled tasks are closed; everything else is open.
+    pub fn is_closed(self) -> bool {
+        matches!(self, Status::Done | Status::Cancelled)
+    }
+}
+
+impl fmt::Display for Status {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.pad(match self {
+            Status::Todo => "todo",
+            Status::InProgress => "in_progress",
+            Status::Blocked => "blocked",
+            Status::Done => "done",
+            Status::Cancelled => "cancelled",
+        })
+    }
+}
+
+impl FromStr for Status {
+    type Err = &'static str;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_ascii_lowercase().replace('-', "_").as_str() {
+            "todo" => Ok(Status::Todo),
+            "in_progress" => Ok(Status::InProgress),
+            "blocked" => Ok(Status::Blocked),
+            "done" => Ok(Status::Done),
+            "cancelled" | "canceled" => Ok(Status::Cancelled),
+            _ => Err("Status must be one of todo, in_progress, blocked, done, cancelled"),
+        }
+    }
+}
+
+/// What a user may do with a task. Ordered from least to most access.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Permission {
+    /// See the task.
+    Viewer,
+    /// Also edit, complete and delete it.
+    Editor,
+    /// Also share, nest, move and archive it. Only the task's creator has
+    /// this; it can't be granted.
+    Owner,
+}
+
+impl fmt::Display for Permission {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.pad(match self {
+            Permission::Viewer => "viewer",
+            Permission::Editor => "editor",
+            Permission::Owner => "owner",
+        })
+    }
+}
+
+impl FromStr for Permission {
+    type Err = &'static str;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_ascii_lowercase().as_str() {
+            "viewer" => Ok(Permission::Viewer),
+            "editor" => Ok(Permission::Editor),
+            _ => Err("Permission must be viewer or editor"),
+        }
+    }
+}
+
+/// A user's role in a [`Workspace`]. Ordered from least to most access.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Role {
+    /// Sees the workspace's tasks.
+    Guest,
+    /// Also edits, completes and deletes them, and adds their own.
+    Member,
+    /// Also manages members and guests, and owns every task in the workspace.
+    Admin,
+    /// Also manages admins and owners, and can delete the workspace.
+    Owner,
+}
+
+impl Role {
+    /// The access this role gives to the workspace's tasks.
+    pub fn task_permission(self) -> Permission {
+        match self {
+            Role::Guest => Permission::Viewer,
+            Role::Member => Permission::Editor,
+            Role::Admin | Role::Owner => Permission::Owner,
+        }
+    }
+}
+
+impl fmt::Display for Role {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.pad(match self {
+            Role::Guest => "guest",
+            Role::Member => "member",
+            Role::Admin => "admin",
+            Role::Owner => "owner",
+        })
+    }
+}
+
+impl FromStr for Role {
+    type Err = &'static str;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_ascii_lowercase().as_str() {
+            "guest" => Ok(Role::Guest),
+            "member" => Ok(Role::Member),
+            "admin" => Ok(Role::Admin),
+            "owner" => Ok(Role::Owner),
+            _ => Err("Role must be one of guest, member, admin, owner"),
+        }
+    }
+}
+
+/// When a task is due: either a moment in time or a whole calendar day.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Due {
+    At(DateTime<Utc>),
+    AllDay(NaiveDate),
+}
+
+impl Due {
+    /// The local calendar day the task is due on.
+    pub fn date(&self) -> NaiveDate {
+        match self {
+            Due::At(at) => at.with_timezone(&Local).date_naive(),
+            Due::AllDay(date) => *date,
+        }
+    }
+
+    /// Whether the deadline has passed. All-day tasks become overdue the day after.
+    pub fn is_past(&self, now: DateTime<Local>) -> bool {
+        match self {
+            Due::At(at) => *at < now,
+            Due::AllDay(date) => *date < now.date_naive(),
+        }
+    }
+}
+
+impl fmt::Display for Due {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Due::At(at) => f.pad(&at.with_timezone(&Local).format("%Y-%m-%d %H:%M").to_string()),
+            Due::AllDay(date) => f.pad(&date.format("%Y-%m-%d").to_string()),
+        }
+    }
+}
+
+impl FromStr for Due {
+    type Err = &'static str;
+
+    /// Accepts `YYYY-MM-DD` for an all-day date, `YYYY-MM-DD HH:MM` in local
+    /// time, or an RFC 3339 timestamp.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let s = s.trim();
+        if let Ok(date) = NaiveDate::parse_from_str(s, "%Y-%m-%d") {
+            return Ok(Due::AllDay(date));
+        }
+        if let Ok(local) = NaiveDateTime::parse_from_str(s, "%Y-%m-%d %H:%M") {
+            if let Some(at) = Local.from_local_datetime(&local).earliest() {
+                return Ok(Due::At(at.with_timezone(&Utc)));
+            }
+        }
+        if let Ok(at) = DateTime::parse_from_rfc3339(s) {
+            return Ok(Due::At(at.with_timezone(&Utc)));
+        }
+        Err("Due date must look like 2024-05-31 or 2024-05-31 17:00")
+    }
+}
+
+/// A single todo item owned by one user.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Task {
+    pub id: u32,
+    pub title: String,
+    pub description: String,
+    #[serde(default)]
+    pub status: Status,
+    // Pre-status files only have `completed`; see `Task::migrate_legacy`.
+    #[serde(default, rename = "completed", skip_serializing)]
+    pub(crate) legacy_completed: Option<bool>,
+    #[serde(default)]
+    pub priority: Priority,
+    #[serde(with = "ts_seconds")]
+    pub created_at: DateTime<Utc>,
+    /// Username of the owner.
+    pub user_id: String,
+    /// Bumped on every change; used to detect edits from another session.
+    #[serde(default)]
+    pub version: u32,
+    /// Deadline. For all-day tasks this is midnight UTC of the due date.
+    #[serde(default, with = "ts_seconds_option")]
+    pub due_at: Option<DateTime<Utc>>,
+    #[serde(default)]
+    pub all_day: bool,
+    /// Lowercase labels, see [`TodoApp::tag_task`](crate::TodoApp::tag_task).
+    #[serde(default)]
+    pub tags: BTreeSet<String>,
+    /// Name of the project the task belongs to, if any.
+    #[serde(default)]
+    pub project: Option<String>,
+    /// Owner of `project` when that isn't the task's owner, e.g. for a task
+    /// a teammate added to a workspace project.
+    #[serde(default)]
+    pub project_owner: Option<String>,
+    /// The task this one is a subtask of.
+    #[serde(default)]
+    pub parent_id: Option<u32>,
+    /// Tasks that must be completed before this one.
+    #[serde(default)]
+    pub blocked_by: BTreeSet<u32>,
+    /// Completing a recurring task creates its next occurrence.
+    #[serde(default)]
+    pub recurrence: Option<Recurrence>,
+    /// When the task first moved to in progress.
+    #[serde(default, with = "ts_seconds_option")]
+    pub started_at: Option<DateTime<Utc>>,
+    #[serde(default, with = "ts_seconds_option")]
+    pub completed_at: Option<DateTime<Utc>>,
+    #[serde(default, with = "ts_seconds_option")]
+    pub cancelled_at: Option<DateTime<Utc>>,
+    /// Set while the task is in the trash.
+    #[serde(default, with = "ts_seconds_option")]
+    pub deleted_at: Option<DateTime<Utc>>,
+    /// Set while the task is in the archive.
+    #[serde(default, with = "ts_seconds_option")]
+    pub archived_at: Option<DateTime<Utc>>,
+    /// Other users with access to this task, and their level.
+    #[serde(default)]
+    pub shared_with: BTreeMap<String, Permission>,
+    /// The user expected to do the task; `user_id` stays its creator.
+    #[serde(default)]
+    pub assignee: Option<String>,
+    /// Every change of `assignee`, oldest first.
+    #[serde(default)]
+    pub assignments: Vec<Assignment>,
+    /// The workspace that owns the task, if any.
+    #[serde(default)]
+    pub workspace: Option<String>,
+}
+
+/// One change of a task's assignee.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Assignment {
+    pub from: Option<String>,
+    pub to: Option<String>,
+    /// Who made the change.
+    pub by: String,
+    #[serde(with = "ts_seconds")]
+    pub at: DateTime<Utc>,
+}
+
+impl Task {
+    /// Whether the task is neither done nor cancelled.
+    pub fn is_open(&self) -> bool {
+        !self.status.is_closed()
+    }
+
+    /// Converts the `completed` flag of records written before tasks had a
+    /// status.
+    pub(crate) fn migrate_legacy(&mut self) {
+        if let Some(completed) = self.legacy_completed.take() {
+            if completed && self.status == Status::Todo {
+                self.status = Status::Done;
+            }
+        }
+    }
+
+    /// Username of the owner of the task's project.
+    pub fn project_user(&self) -> &str {
+        self.project_owner.as_deref().unwrap_or(&self.user_id)
+    }
+
+    /// Whether the task is in `project` or one of its subprojects.
+    pub fn in_project(&self, project: &Project) -> bool {
+        project.user_id == self.project_user()
+            && self.project.as_deref().is_some_and(|name| project.contains(name))
+    }
+
+    /// Puts the task into `project`, or out of any project when `None`.
+    pub fn set_project(&mut self, project: Option<&Project>) {
+        self.project = project.map(|project| project.name.clone());
+        self.project_owner = project
+            .map(|project| project.user_id.clone())
+            .filter(|owner| *owner != self.user_id);
+    }
+
+    pub fn due(&self) -> Option<Due> {
+        let due_at = self.due_at?;
+        Some(if self.all_day { Due::AllDay(due_at.date_naive()) } else { Due::At(due_at) })
+    }
+
+    pub fn set_due(&mut self, due: Option<Due>) {
+        (self.due_at, self.all_day) = match due {
+            Some(Due::At(at)) => (Some(at), false),
+            Some(Due::AllDay(date)) => (Some(date.and_time(Default::default()).and_utc()), true),
+            None => (None, false),
+        };
+    }
+
+    /// Whether the task is still open and its deadline has passed.
+    pub fn is_overdue(&self, now: DateTime<Local>) -> bool {
+        self.is_open() && self.due().is_some_and(|due| due.is_past(now))
+    }
+}
+
+/// A named group of one user's tasks. Projects nest through `/`-separated
+/// names: `work/client-a` is a subproject of `work`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Project {
+    pub name: String,
+    pub user_id: String,
+    #[serde(with = "ts_seconds")]
+    pub created_at: DateTime<Utc>,
+    /// Other users with access to this project's tasks, and their level.
+    #[serde(default)]
+    pub shared_with: BTreeMap<String, Permission>,
+    /// The workspace whose members get access to this project's tasks.
+    #[serde(default)]
+    pub workspace: Option<String>,
+}
+
+impl Project {
+    /// Name of the enclosing project, if this one is nested.
+    pub fn parent(&self) -> Option<&str> {
+        self.name.rsplit_once('/').map(|(parent, _)| parent)
+    }
+
+    /// Whether `name` is this project or one of its subprojects.
+    pub fn contains(&self, name: &str) -> bool {
+        name.strip_prefix(self.name.as_str())
+            .is_some_and(|rest| rest.is_empty() || rest.starts_with('/'))
+    }
+}
+
+/// A group of users sharing tasks and projects. Members' roles decide what
+/// they may do with the workspace's tasks.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Workspace {
+    pub name: String,
+    #[serde(with = "ts_seconds")]
+    pub created_at: DateTime<Utc>,
+    pub members: BTreeMap<String, Role>,
+}
+
+/// Completed versus total tasks in a group.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Progress {
+    pub done: usize,
+    pub total: usize,
+}
+
+impl Progress {
+    /// Percentage of completed tasks; an empty group counts as 0%.
+    pub fn percent(&self) -> f64 {
+        if self.total == 0 {
+            0.0
+        } else {
+            self.done as f64 * 100.0 / self.total as f64
+        }
+    }
+}
+
+/// A registered account.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct User {
+    pub username: String,
+    // Argon2id PHC string. Older users.json files store the plaintext password
+    // under `password`; those entries are rehashed on the next successful login.
+    #[serde(rename = "password_hash", alias = "password")]
+    pub(crate) password: String,
+    /// Administrators can manage other accounts.
+    #[serde(default)]
+    pub admin: bool,
+    /// Locked accounts can't log in.
+    #[serde(default)]
+    pub locked: bool,
+    /// Unknown for accounts registered before it was recorded.
+    #[serde(default, with = "ts_seconds_option")]
+    pub registered_at: Option<DateTime<Utc>>,
+}
@@ -0,0 +1,186 @@
+use std::collections::BTreeMap;
+use std::io::{self, IsTerminal, Write};
+
+use chrono::{Local, NaiveDate};
+use clap::ValueEnum;
+use serde_json::Value;
+use todo::{Due, Status, Task};
+
+/// Output format for task listings. `json`, `jsonl` and `csv` use the field
+/// names of the serialized [`Task`].
+#[derive(Clone, Copy, ValueEnum)]
+pub enum Format {
+    Table,
+    Json,
+    Jsonl,
+    Csv,
+}
+
+/// Writes `tasks` in `format`. The table marks open tasks for which
+/// `is_blocked` returns true, and names the owner and assignee when they
+/// aren't `viewer`.
+pub fn write_tasks(
+    out: &mut impl Write,
+    tasks: &[&Task],
+    format: Format,
+    viewer: &str,
+    is_blocked: impl Fn(&Task) -> bool,
+) -> io::Result<()> {
+    match format {
+        Format::Table => write_table(out, tasks, viewer, is_blocked),
+        Format::Json => {
+            serde_json::to_writer_pretty(&mut *out, tasks)?;
+            writeln!(out)
+        }
+        Format::Jsonl => {
+            for task in tasks {
+                serde_json::to_writer(&mut *out, task)?;
+                writeln!(out)?;
+            }
+            Ok(())
+        }
+        Format::Csv => write_csv(out, tasks),
+    }
+}
+
+/// Orders `tasks` so subtasks directly follow their parent, keeping the given
+/// order among siblings, and pairs each task with its depth in the tree. Tasks
+/// whose parent isn't listed are shown at the top level.
+fn as_tree<'a>(tasks: &[&'a Task]) -> Vec<(usize, &'a Task)> {
+    fn visit<'a>(task: &'a Task, depth: usize, tasks: &[&'a Task], rows: &mut Vec<(usize, &'a Task)>) {
+        rows.push((depth, task));
+        for child in tasks.iter().filter(|child| child.parent_id == Some(task.id)) {
+            visit(child, depth + 1, tasks, rows);
+        }
+    }
+
+    let mut rows = Vec::with_capacity(tasks.len());
+    for task in tasks {
+        let parent_listed = task.parent_id.is_some_and(|parent| tasks.iter().any(|t| t.id == parent));
+        if !parent_listed {
+            visit(task, 0, tasks, &mut rows);
+        }
+    }
+    rows
+}
+
+fn write_table(out: &mut impl Write, tasks: &[&Task], viewer: &str, is_blocked: impl Fn(&Task) -> bool) -> io::Result<()> {
+    let width = tasks.iter().map(|task| task.id.to_string().len()).max().unwrap_or(0).max(2);
+
+    writeln!(out, "{:>width$}  {:<11}  {:<8}  {:<16}  TITLE", "ID", "STATUS", "PRIORITY", "DUE")?;
+    for (depth, task) in as_tree(tasks) {
+        let status = if is_blocked(task) { Status::Blocked } else { task.status };
+        let due = task.due().map(|due| due.to_string()).unwrap_or_default();
+        let project = task.project.as_ref().map(|project| format!(" @{}", project)).unwrap_or_default();
+        let tags: String = task.tags.iter().map(|tag| format!(" #{}", tag)).collect();
+        let owner = if task.user_id == viewer { String::new() } else { format!(" (from {})", task.user_id) };
+        let assignee = match &task.assignee {
+            Some(assignee) if assignee != viewer => format!(" (assigned to {})", assignee),
+            _ => String::new(),
+        };
+        let indent = if depth == 0 { String::new() } else { format!("{}└ ", "  ".repeat(depth - 1)) };
+        writeln!(out, "{:>width$}  {:<11}  {:<8}  {:<16}  {}{}{}{}{}{}", task.id, status, task.priority, due, indent, task.title, project, tags, owner, assignee)?;
+    }
+    Ok(())
+}
+
+/// CSV columns: the serialized [`Task`] fields, in declaration order. Fixed
+/// so an empty listing still has a header.
+const CSV_COLUMNS: &[&str] = &[
+    "id", "title", "description", "status", "priority", "created_at", "user_id", "version",
+    "due_at", "all_day", "tags", "project", "project_owner", "parent_id", "blocked_by",
+    "recurrence", "started_at", "completed_at", "cancelled_at", "deleted_at", "archived_at",
+    "shared_with", "assignee", "assignments", "workspace",
+];
+
+fn write_csv(out: &mut impl Write, tasks: &[&Task]) -> io::Result<()> {
+    writeln!(out, "{}", CSV_COLUMNS.join(","))?;
+
+    for task in tasks {
+        let Value::Object(row) = serde_json::to_value(task)? else {
+            unreachable!("tasks serialize to objects");
+        };
+        let cells: Vec<String> = CSV_COLUMNS.iter()
+            .map(|key| csv_cell(row.get(*key).unwrap_or(&Value::Null)))
+            .collect();
+        writeln!(out, "{}", cells.join(","))?;
+    }
+    Ok(())
+}
+
+fn csv_cell(value: &Value) -> String {
+    let text = match value {
+        Value::Null => String::new(),
+        Value::String(s) => s.clone(),
+        other => other.to_string(),
+    };
+    if text.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", text.replace('"', "\"\""))
+    } else {
+        text
+    }
+}
+
+/// Prints open tasks grouped by due day. Overdue tasks are flagged, and shown
+/// in red when writing to a terminal.
+pub fn write_agenda(out: &mut impl Write, agenda: &BTreeMap<NaiveDate, Vec<&Task>>) -> io::Result<()> {
+    let now = Local::now();
+    let color = io::stdout().is_terminal();
+
+    if agenda.is_empty() {
+        return writeln!(out, "Nothing due.");
+    }
+    for (day, tasks) in agenda {
+        let label = match (*day - now.date_naive()).num_days() {
+            0 => " (today)",
+            1 => " (tomorrow)",
+            _ => "",
+        };
+        writeln!(out, "\n{}{}", day.format("%a %Y-%m-%d"), label)?;
+
+        for task in tasks {
+            let time = match task.due() {
+                Some(Due::At(at)) => at.with_timezone(&Local).format("%H:%M").to_string(),
+                _ => "all day".to_string(),
+            };
+            let line = format!("  {:>7}  #{} {} [{}]", time, task.id, task.title, task.priority);
+            if !task.is_overdue(now) {
+                writeln!(out, "{}", line)?;
+            } else if color {
+                writeln!(out, "\x1b[31m{} OVERDUE\x1b[0m", line)?;
+            } else {
+                writeln!(out, "{} OVERDUE", line)?;
+            }
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use todo::storage::MemoryStorage;
+    use todo::{Priority, TodoApp};
+
+    use super::*;
+
+    #[test]
+    fn csv_columns_match_task_fields() {
+        let mut app = TodoApp::with_storage(Box::new(MemoryStorage::default()));
+        app.register("alice".into(), "secret".into()).unwrap();
+        app.login("alice".into(), "secret".into()).unwrap();
+        let id = app.add_task("title".into(), String::new(), Priority::Normal, None).unwrap();
+
+        let Value::Object(fields) = serde_json::to_value(app.get_task(id).unwrap()).unwrap() else {
+            panic!("tasks serialize to objects");
+        };
+        let keys: Vec<&str> = fields.keys().map(String::as_str).collect();
+        assert_eq!(keys, CSV_COLUMNS);
+    }
+
+    #[test]
+    fn empty_csv_has_header() {
+        let mut out = Vec::new();
+        write_tasks(&mut out, &[], Format::Csv, "", |_| false).unwrap();
+        assert_eq!(String::from_utf8(out).unwrap(), format!("{}\n", CSV_COLUMNS.join(",")));
+    }
+}
@@ -0,0 +1,335 @@
+use std::fmt;
+use std::str::FromStr;
+
+use chrono::{DateTime, Datelike, Days, Local, Months, NaiveDate, TimeZone, Utc, Weekday};
+use serde::{Deserialize, Serialize};
+
+use crate::model::Due;
+
+/// How a task repeats once completed.
+///
+/// Parsed from a short form (`daily`, `every 3 days`, `weekly on mon,thu`,
+/// `monthly on 15`, `every 10 days after completion`) or from an RFC 5545
+/// `RRULE` using `FREQ=DAILY|WEEKLY|MONTHLY`, `INTERVAL`, `BYDAY` and
+/// `BYMONTHDAY`.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "freq", rename_all = "snake_case")]
+pub enum Recurrence {
+    /// Every `interval` days after the previous due date.
+    Daily { interval: u32 },
+    /// Every `interval` weeks, on `weekdays` (or the due date's weekday if empty).
+    Weekly { interval: u32, weekdays: Vec<Weekday> },
+    /// Every `interval` months on day `day`, clamped to the month's length.
+    Monthly { interval: u32, day: u32 },
+    /// `days` days after the task was actually completed.
+    AfterCompletion { days: u32 },
+}
+
+impl Recurrence {
+    /// Rejects the zero intervals, days and day numbers the parser refuses,
+    /// for rules built or deserialized directly.
+    pub fn validate(&self) -> Result<(), &'static str> {
+        let positive = match self {
+            Recurrence::Daily { interval } | Recurrence::Weekly { interval, .. } => *interval > 0,
+            Recurrence::Monthly { interval, day } => *interval > 0 && *day > 0,
+            Recurrence::AfterCompletion { days } => *days > 0,
+        };
+        if positive {
+            Ok(())
+        } else {
+            Err("Recurrence numbers must be positive integers")
+        }
+    }
+
+    /// The date of the occurrence after one due on `due` and completed on
+    /// `completed`. A weekly interval or month day of zero, which
+    /// [`validate`](Self::validate) rejects, is treated as 1.
+    pub fn next_date(&self, due: NaiveDate, completed: NaiveDate) -> NaiveDate {
+        match self {
+            Recurrence::Daily { interval } => due + Days::new(u64::from(*interval)),
+            Recurrence::Weekly { interval, weekdays } if weekdays.is_empty() => {
+                due + Days::new(7 * u64::from(*interval))
+            }
+            Recurrence::Weekly { interval, weekdays } => {
+                let interval = i64::from((*interval).max(1));
+                let week_start = due.week(Weekday::Mon).first_day();
+                due.iter_days()
+                    .skip(1)
+                    .find(|day| {
+                        let weeks = (day.week(Weekday::Mon).first_day() - week_start).num_weeks();
+                        weeks % interval == 0 && weekdays.contains(&day.weekday())
+                    })
+                    .expect("a matching weekday occurs within the interval")
+            }
+            Recurrence::Monthly { interval, day } => {
+                let month = NaiveDate::from_ymd_opt(due.year(), due.month(), 1)
+                    .expect("first of month is valid")
+                    + Months::new(*interval);
+                let last = (month + Months::new(1)).pred_opt().expect("month has a last day").day();
+                month.with_day((*day).clamp(1, last)).expect("day is clamped to the month")
+            }
+            Recurrence::AfterCompletion { days } => completed + Days::new(u64::from(*days)),
+        }
+    }
+
+    /// The due date of the occurrence after one due at `due` (or undated),
+    /// completed at `now`. Keeps the time of day for timed tasks.
+    pub fn next_due(&self, due: Option<Due>, now: DateTime<Local>) -> Due {
+        let today = now.date_naive();
+        match due {
+            None => Due::AllDay(self.next_date(today, today)),
+            Some(Due::AllDay(date)) => Due::AllDay(self.next_date(date, today)),
+            Some(Due::At(at)) => {
+                let local = at.with_timezone(&Local);
+                let date = self.next_date(local.date_naive(), today);
+                let next = Local.from_local_datetime(&date.and_time(local.time()))
+                    .earliest()
+                    .map_or(at, |next| next.with_timezone(&Utc));
+                Due::At(next)
+            }
+        }
+    }
+}
+
+fn weekday_code(day: Weekday) -> &'static str {
+    match day {
+        Weekday::Mon => "MO",
+        Weekday::Tue => "TU",
+        Weekday::Wed => "WE",
+        Weekday::Thu => "TH",
+        Weekday::Fri => "FR",
+        Weekday::Sat => "SA",
+        Weekday::Sun => "SU",
+    }
+}
+
+fn parse_weekday(s: &str) -> Result<Weekday, &'static str> {
+    match s.trim().to_ascii_uppercase().as_str() {
+        "MO" | "MON" | "MONDAY" => Ok(Weekday::Mon),
+        "TU" | "TUE" | "TUESDAY" => Ok(Weekday::Tue),
+        "WE" | "WED" | "WEDNESDAY" => Ok(Weekday::Wed),
+        "TH" | "THU" | "THURSDAY" => Ok(Weekday::Thu),
+        "FR" | "FRI" | "FRIDAY" => Ok(Weekday::Fri),
+        "SA" | "SAT" | "SATURDAY" => Ok(Weekday::Sat),
+        "SU" | "SUN" | "SUNDAY" => Ok(Weekday::Sun),
+        _ => Err("Unknown weekday"),
+    }
+}
+
+fn parse_number(s: &str) -> Result<u32, &'static str> {
+    match s.trim().parse() {
+        Ok(n) if n > 0 => Ok(n),
+        _ => Err("Recurrence numbers must be positive integers"),
+    }
+}
+
+fn parse_rrule(rule: &str) -> Result<Recurrence, &'static str> {
+    let mut freq = None;
+    let mut interval = 1;
+    let mut weekdays = Vec::new();
+    let mut month_day = None;
+
+    for part in rule.split(';').filter(|part| !part.is_empty()) {
+        let (key, value) = part.split_once('=').ok_or("Malformed RRULE")?;
+        match key.to_ascii_uppercase().as_str() {
+            "FREQ" => freq = Some(value.to_ascii_uppercase()),
+            "INTERVAL" => interval = parse_number(value)?,
+            "BYDAY" => weekdays = value.split(',').map(parse_weekday).collect::<Result<_, _>>()?,
+            "BYMONTHDAY" => month_day = Some(parse_number(value)?.min(31)),
+            _ => return Err("Unsupported RRULE part; use FREQ, INTERVAL, BYDAY or BYMONTHDAY"),
+        }
+    }
+
+    match freq.as_deref() {
+        Some("DAILY") => Ok(Recurrence::Daily { interval }),
+        Some("WEEKLY") => Ok(Recurrence::Weekly { interval, weekdays }),
+        Some("MONTHLY") => Ok(Recurrence::Monthly { interval, day: month_day.ok_or("Monthly RRULE needs BYMONTHDAY")? }),
+        _ => Err("RRULE FREQ must be DAILY, WEEKLY or MONTHLY"),
+    }
+}
+
+impl FromStr for Recurrence {
+    type Err = &'static str;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let s = s.trim();
+        if let Some(rule) = s.strip_prefix("RRULE:").or_else(|| s.strip_prefix("rrule:")) {
+            return parse_rrule(rule);
+        }
+
+        let lower = s.to_ascii_lowercase();
+        let words: Vec<&str> = lower.split_whitespace().collect();
+        match words.as_slice() {
+            ["daily"] => Ok(Recurrence::Daily { interval: 1 }),
+            ["weekly"] => Ok(Recurrence::Weekly { interval: 1, weekdays: Vec::new() }),
+            ["weekly", "on", days] => Ok(Recurrence::Weekly {
+                interval: 1,
+                weekdays: days.split(',').map(parse_weekday).collect::<Result<_, _>>()?,
+            }),
+            ["monthly", "on", day] => Ok(Recurrence::Monthly { interval: 1, day: parse_number(day)?.min(31) }),
+            ["every", n, "days"] => Ok(Recurrence::Daily { interval: parse_number(n)? }),
+            ["every", n, "days", "after", "completion"] => Ok(Recurrence::AfterCompletion { days: parse_number(n)? }),
+            _ => Err("Unknown recurrence; try daily, weekly on mon,thu, monthly on 15, every 3 days, every 10 days after completion or RRULE:FREQ=..."),
+        }
+    }
+}
+
+impl fmt::Display for Recurrence {
+    /// Formats as an `RRULE`, except for completion-based rules which have no
+    /// RFC 5545 equivalent.
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Recurrence::Daily { interval } => write!(f, "RRULE:FREQ=DAILY;INTERVAL={}", interval),
+            Recurrence::Weekly { interval, weekdays } if weekdays.is_empty() => {
+                write!(f, "RRULE:FREQ=WEEKLY;INTERVAL={}", interval)
+            }
+            Recurrence::Weekly { interval, weekdays } => {
+                let days: Vec<&str> = weekdays.iter().map(|day| weekday_code(*day)).collect();
+                write!(f, "RRULE:FREQ=WEEKLY;INTERVAL={};BYDAY={}", interval, days.join(","))
+            }
+            Recurrence::Monthly { interval, day } => {
+                write!(f, "RRULE:FREQ=MONTHLY;INTERVAL={};BYMONTHDAY={}", interval, day)
+            }
+            Recurrence::AfterCompletion { days } => write!(f, "every {} days after completion", days),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn date(y: i32, m: u32, d: u32) -> NaiveDate {
+        NaiveDate::from_ymd_opt(y, m, d).unwrap()
+    }
+
+    #[test]
+    fn daily_adds_interval_to_due_date() {
+        let rule = Recurrence::Daily { interval: 3 };
+        assert_eq!(rule.next_date(date(2026, 2, 27), date(2026, 3, 10)), date(2026, 3, 2));
+    }
+
+    #[test]
+    fn weekly_without_weekdays_repeats_due_weekday() {
+        let rule = Recurrence::Weekly { interval: 2, weekdays: Vec::new() };
+        assert_eq!(rule.next_date(date(2026, 10, 14), date(2026, 10, 14)), date(2026, 10, 28));
+    }
+
+    #[test]
+    fn weekly_picks_next_weekday_in_same_week() {
+        let rule = Recurrence::Weekly { interval: 1, weekdays: vec![Weekday::Mon, Weekday::Thu] };
+        // Monday 2026-10-12 -> Thursday 2026-10-15 -> Monday 2026-10-19.
+        assert_eq!(rule.next_date(date(2026, 10, 12), date(2026, 10, 12)), date(2026, 10, 15));
+        assert_eq!(rule.next_date(date(2026, 10, 15), date(2026, 10, 15)), date(2026, 10, 19));
+    }
+
+    #[test]
+    fn weekly_with_interval_skips_weeks() {
+        let rule = Recurrence::Weekly { interval: 2, weekdays: vec![Weekday::Mon, Weekday::Thu] };
+        // From Thursday, the next matching week starts two weeks after Monday 2026-10-12.
+        assert_eq!(rule.next_date(date(2026, 10, 15), date(2026, 10, 15)), date(2026, 10, 26));
+        assert_eq!(rule.next_date(date(2026, 10, 12), date(2026, 10, 12)), date(2026, 10, 15));
+    }
+
+    #[test]
+    fn monthly_clamps_to_month_length() {
+        let rule = Recurrence::Monthly { interval: 1, day: 31 };
+        assert_eq!(rule.next_date(date(2026, 1, 31), date(2026, 1, 31)), date(2026, 2, 28));
+        assert_eq!(rule.next_date(date(2028, 1, 31), date(2028, 1, 31)), date(2028, 2, 29));
+        assert_eq!(rule.next_date(date(2026, 2, 28), date(2026, 2, 28)), date(2026, 3, 31));
+    }
+
+    #[test]
+    fn monthly_interval_crosses_year() {
+        let rule = Recurrence::Monthly { interval: 3, day: 15 };
+        assert_eq!(rule.next_date(date(2026, 11, 15), date(2026, 11, 20)), date(2027, 2, 15));
+    }
+
+    #[test]
+    fn after_completion_counts_from_completion() {
+        let rule = Recurrence::AfterCompletion { days: 10 };
+        assert_eq!(rule.next_date(date(2026, 1, 1), date(2026, 1, 5)), date(2026, 1, 15));
+    }
+
+    #[test]
+    fn next_due_keeps_local_time_across_dst_changes() {
+        let rule = Recurrence::Daily { interval: 1 };
+        // Days before the EU and US spring and autumn transitions. Noon exists
+        // on both sides in every zone, so the local time must be unchanged
+        // whatever the machine's time zone is.
+        for (y, m, d) in [(2026, 3, 7), (2026, 3, 28), (2026, 10, 24), (2026, 10, 31)] {
+            let at = Local.with_ymd_and_hms(y, m, d, 12, 0, 0).unwrap().with_timezone(&Utc);
+            let Due::At(next) = rule.next_due(Some(Due::At(at)), at.with_timezone(&Local)) else {
+                panic!("timed task must stay timed");
+            };
+            let next = next.with_timezone(&Local);
+            assert_eq!(next.date_naive(), date(y, m, d) + Days::new(1));
+            assert_eq!(next.time(), at.with_timezone(&Local).time());
+        }
+    }
+
+    #[test]
+    fn next_due_of_undated_task_counts_from_today() {
+        let now = Local.with_ymd_and_hms(2026, 10, 18, 9, 0, 0).unwrap();
+        let rule = Recurrence::Daily { interval: 2 };
+        assert_eq!(rule.next_due(None, now), Due::AllDay(date(2026, 10, 20)));
+    }
+
+    #[test]
+    fn parses_short_forms() {
+        assert_eq!("daily".parse(), Ok(Recurrence::Daily { interval: 1 }));
+        assert_eq!("every 3 days".parse(), Ok(Recurrence::Daily { interval: 3 }));
+        assert_eq!(
+            "Weekly on mon,THU".parse(),
+            Ok(Recurrence::Weekly { interval: 1, weekdays: vec![Weekday::Mon, Weekday::Thu] })
+        );
+        assert_eq!("monthly on 40".parse(), Ok(Recurrence::Monthly { interval: 1, day: 31 }));
+        assert_eq!("every 10 days after completion".parse(), Ok(Recurrence::AfterCompletion { days: 10 }));
+        assert!("every 0 days".parse::<Recurrence>().is_err());
+        assert!("hourly".parse::<Recurrence>().is_err());
+    }
+
+    #[test]
+    fn parses_rrules() {
+        assert_eq!("RRULE:FREQ=DAILY;INTERVAL=2".parse(), Ok(Recurrence::Daily { interval: 2 }));
+        assert_eq!(
+            "rrule:freq=weekly;byday=MO,FR".parse(),
+            Ok(Recurrence::Weekly { interval: 1, weekdays: vec![Weekday::Mon, Weekday::Fri] })
+        );
+        assert_eq!(
+            "RRULE:FREQ=MONTHLY;INTERVAL=3;BYMONTHDAY=15".parse(),
+            Ok(Recurrence::Monthly { interval: 3, day: 15 })
+        );
+        assert!("RRULE:FREQ=MONTHLY".parse::<Recurrence>().is_err());
+        assert!("RRULE:FREQ=YEARLY".parse::<Recurrence>().is_err());
+        assert!("RRULE:FREQ=DAILY;COUNT=5".parse::<Recurrence>().is_err());
+        assert!("RRULE:FREQ".parse::<Recurrence>().is_err());
+    }
+
+    #[test]
+    fn display_round_trips_through_parse() {
+        let rules = [
+            Recurrence::Daily { interval: 4 },
+            Recurrence::Weekly { interval: 1, weekdays: Vec::new() },
+            Recurrence::Weekly { interval: 2, weekdays: vec![Weekday::Tue, Weekday::Sun] },
+            Recurrence::Monthly { interval: 1, day: 31 },
+            Recurrence::AfterCompletion { days: 7 },
+        ];
+        for rule in rules {
+            assert_eq!(rule.to_string().parse(), Ok(rule));
+        }
+    }
+
+    #[test]
+    fn zero_numbers_fail_validation_but_do_not_panic() {
+        let weekly = Recurrence::Weekly { interval: 0, weekdays: vec![Weekday::Mon] };
+        let monthly = Recurrence::Monthly { interval: 1, day: 0 };
+        assert!(weekly.validate().is_err());
+        assert!(monthly.validate().is_err());
+        assert!(Recurrence::Daily { interval: 0 }.validate().is_err());
+        assert!(Recurrence::Monthly { interval: 1, day: 31 }.validate().is_ok());
+
+        assert_eq!(weekly.next_date(date(2026, 10, 12), date(2026, 10, 12)), date(2026, 10, 19));
+        assert_eq!(monthly.next_date(date(2026, 10, 12), date(2026, 10, 12)), date(2026, 11, 1));
+    }
+}
@@ -0,0 +1,183 @@
+use std::collections::HashMap;
+use std::ffi::OsString;
+use std::fs::{self, File};
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
+
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+
+use super::{Storage, StorageLock};
+use crate::error::Result;
+use crate::model::{Project, Task, User, Workspace};
+
+/// Stores tasks and users as two JSON files. The task ID counter lives next
+/// to the tasks file with a `.seq` suffix; projects, workspaces, deleted and
+/// archived tasks go in `projects.json`, `workspaces.json`, `trash.json` and
+/// `archive.json` in the same directory.
+///
+/// Writes go to a temporary file that is synced and renamed over the target,
+/// so a crash leaves either the old or the new version in place. The previous
+/// version is kept next to it with a `.bak` suffix and is used when the
+/// primary file can't be parsed.
+pub struct JsonStorage {
+    tasks_path: PathBuf,
+    users_path: PathBuf,
+}
+
+impl JsonStorage {
+    pub fn new(tasks_path: impl Into<PathBuf>, users_path: impl Into<PathBuf>) -> Self {
+        Self {
+            tasks_path: tasks_path.into(),
+            users_path: users_path.into(),
+        }
+    }
+}
+
+impl Default for JsonStorage {
+    /// `tasks.json` and `users.json` in the working directory.
+    fn default() -> Self {
+        Self::new("tasks.json", "users.json")
+    }
+}
+
+fn with_suffix(path: &Path, suffix: &str) -> PathBuf {
+    let mut name = OsString::from(path.as_os_str());
+    name.push(suffix);
+    PathBuf::from(name)
+}
+
+fn parse_file<T: DeserializeOwned + Default>(path: &Path) -> Result<Option<T>> {
+    match fs::read_to_string(path) {
+        // A zero-length file is what an interrupted plain write leaves behind.
+        Ok(contents) if contents.trim().is_empty() => Ok(None),
+        Ok(contents) => Ok(Some(serde_json::from_str(&contents)?)),
+        Err(e) if e.kind() == io::ErrorKind::NotFound => Ok(None),
+        Err(e) => Err(e.into()),
+    }
+}
+
+fn read_json<T: DeserializeOwned + Default>(path: &Path) -> Result<T> {
+    let backup = with_suffix(path, ".bak");
+
+    match parse_file(path) {
+        Ok(Some(value)) => Ok(value),
+        Ok(None) if !path.exists() => Ok(T::default()),
+        primary => match parse_file(&backup) {
+            Ok(Some(value)) => {
+                eprintln!(
+                    "Warning: {} is empty or unreadable, loaded {} instead",
+                    path.display(),
+                    backup.display()
+                );
+                Ok(value)
+            }
+            _ => primary.map(Option::unwrap_or_default),
+        },
+    }
+}
+
+fn write_json<T: Serialize>(path: &Path, value: &T) -> Result<()> {
+    let json = serde_json::to_string(value)?;
+    let tmp = with_suffix(path, ".tmp");
+
+    let mut file = File::create(&tmp)?;
+    file.write_all(json.as_bytes())?;
+    file.sync_all()?;
+    drop(file);
+
+    if path.exists() {
+        fs::copy(path, with_suffix(path, ".bak"))?;
+    }
+    fs::rename(&tmp, path)?;
+
+    // Make the rename itself durable.
+    #[cfg(unix)]
+    if let Some(dir) = path.parent() {
+        let dir = if dir.as_os_str().is_empty() { Path::new(".") } else { dir };
+        File::open(dir)?.sync_all()?;
+    }
+    Ok(())
+}
+
+impl Storage for JsonStorage {
+    fn load_tasks(&mut self) -> Result<HashMap<u32, Task>> {
+        read_json(&self.tasks_path)
+    }
+
+    fn save_tasks(&mut self, tasks: &HashMap<u32, Task>) -> Result<()> {
+        write_json(&self.tasks_path, tasks)
+    }
+
+    fn load_users(&mut self) -> Result<HashMap<String, User>> {
+        read_json(&self.users_path)
+    }
+
+    fn save_users(&mut self, users: &HashMap<String, User>) -> Result<()> {
+        write_json(&self.users_path, users)
+    }
+
+    fn load_next_task_id(&mut self) -> Result<Option<u32>> {
+        read_json(&with_suffix(&self.tasks_path, ".seq"))
+    }
+
+    fn save_next_task_id(&mut self, id: u32) -> Result<()> {
+        write_json(&with_suffix(&self.tasks_path, ".seq"), &id)
+    }
+
+    fn load_projects(&mut self) -> Result<Vec<Project>> {
+        read_json(&self.tasks_path.with_file_name("projects.json"))
+    }
+
+    fn save_projects(&mut self, projects: &[Project]) -> Result<()> {
+        write_json(&self.tasks_path.with_file_name("projects.json"), &projects)
+    }
+
+    fn load_workspaces(&mut self) -> Result<Vec<Workspace>> {
+        read_json(&self.tasks_path.with_file_name("workspaces.json"))
+    }
+
+    fn save_workspaces(&mut self, workspaces: &[Workspace]) -> Result<()> {
+        write_json(&self.tasks_path.with_file_name("workspaces.json"), &workspaces)
+    }
+
+    fn load_trash(&mut self) -> Result<HashMap<u32, Task>> {
+        read_json(&self.tasks_path.with_file_name("trash.json"))
+    }
+
+    fn save_trash(&mut self, trash: &HashMap<u32, Task>) -> Result<()> {
+        write_json(&self.tasks_path.with_file_name("trash.json"), trash)
+    }
+
+    fn load_archive(&mut self) -> Result<HashMap<u32, Task>> {
+        read_json(&self.tasks_path.with_file_name("archive.json"))
+    }
+
+    fn save_archive(&mut self, archive: &HashMap<u32, Task>) -> Result<()> {
+        write_json(&self.tasks_path.with_file_name("archive.json"), archive)
+    }
+
+    fn lock(&mut self) -> Result<StorageLock> {
+        StorageLock::acquire(&with_suffix(&self.tasks_path, ".lock"))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::app::tests::temp_dir;
+
+    #[test]
+    fn unreadable_file_falls_back_to_backup() {
+        let dir = temp_dir("backup");
+        let path = dir.join("numbers.json");
+        write_json(&path, &vec![1]).unwrap();
+        write_json(&path, &vec![1, 2]).unwrap();
+
+        fs::write(&path, "[1, 2").unwrap();
+        assert_eq!(read_json::<Vec<u32>>(&path).unwrap(), vec![1]);
+        fs::write(&path, "").unwrap();
+        assert_eq!(read_json::<Vec<u32>>(&path).unwrap(), vec![1]);
+        fs::remove_dir_all(dir).unwrap();
+    }
+}
@@ -0,0 +1,82 @@
+use std::collections::HashMap;
+
+use super::Storage;
+use crate::error::Result;
+use crate::model::{Project, Task, User, Workspace};
+
+/// Keeps everything in memory; nothing survives the process. Useful for tests.
+#[derive(Default)]
+pub struct MemoryStorage {
+    tasks: HashMap<u32, Task>,
+    users: HashMap<String, User>,
+    next_task_id: Option<u32>,
+    projects: Vec<Project>,
+    workspaces: Vec<Workspace>,
+    trash: HashMap<u32, Task>,
+    archive: HashMap<u32, Task>,
+}
+
+impl Storage for MemoryStorage {
+    fn load_tasks(&mut self) -> Result<HashMap<u32, Task>> {
+        Ok(self.tasks.clone())
+    }
+
+    fn save_tasks(&mut self, tasks: &HashMap<u32, Task>) -> Result<()> {
+        self.tasks = tasks.clone();
+        Ok(())
+    }
+
+    fn load_users(&mut self) -> Result<HashMap<String, User>> {
+        Ok(self.users.clone())
+    }
+
+    fn save_users(&mut self, users: &HashMap<String, User>) -> Result<()> {
+        self.users = users.clone();
+        Ok(())
+    }
+
+    fn load_next_task_id(&mut self) -> Result<Option<u32>> {
+        Ok(self.next_task_id)
+    }
+
+    fn save_next_task_id(&mut self, id: u32) -> Result<()> {
+        self.next_task_id = Some(id);
+        Ok(())
+    }
+
+    fn load_projects(&mut self) -> Result<Vec<Project>> {
+        Ok(self.projects.clone())
+    }
+
+    fn save_projects(&mut self, projects: &[Project]) -> Result<()> {
+        self.projects = projects.to_vec();
+        Ok(())
+    }
+
+    fn load_workspaces(&mut self) -> Result<Vec<Workspace>> {
+        Ok(self.workspaces.clone())
+    }
+
+    fn save_workspaces(&mut self, workspaces: &[Workspace]) -> Result<()> {
+        self.workspaces = workspaces.to_vec();
+        Ok(())
+    }
+
+    fn load_trash(&mut self) -> Result<HashMap<u32, Task>> {
+        Ok(self.trash.clone())
+    }
+
+    fn save_trash(&mut self, trash: &HashMap<u32, Task>) -> Result<()> {
+        self.trash = trash.clone();
+        Ok(())
+    }
+
+    fn load_archive(&mut self) -> Result<HashMap<u32, Task>> {
+        Ok(self.archive.clone())
+    }
+
+    fn save_archive(&mut self, archive: &HashMap<u32, Task>) -> Result<()> {
+        self.archive = archive.clone();
+        Ok(())
+    }
+}
@@ -0,0 +1,129 @@
+//! Persistence backends for [`TodoApp`](crate::TodoApp).
+
+mod json;
+mod memory;
+mod sqlite;
+
+use std::collections::HashMap;
+use std::fs::{File, OpenOptions};
+use std::path::Path;
+
+use crate::error::{Result, TodoError};
+use crate::model::{Project, Task, User, Workspace};
+
+pub use json::JsonStorage;
+pub use memory::MemoryStorage;
+pub use sqlite::SqliteStorage;
+
+/// Where users and tasks are persisted.
+///
+/// `save_*` receives the complete current state; implementations replace
+/// whatever they stored previously.
+pub trait Storage {
+    fn load_tasks(&mut self) -> Result<HashMap<u32, Task>>;
+    fn save_tasks(&mut self, tasks: &HashMap<u32, Task>) -> Result<()>;
+    fn load_users(&mut self) -> Result<HashMap<String, User>>;
+    fn save_users(&mut self, users: &HashMap<String, User>) -> Result<()>;
+
+    /// Returns the ID the next new task will get, if one has been recorded.
+    fn load_next_task_id(&mut self) -> Result<Option<u32>>;
+    fn save_next_task_id(&mut self, id: u32) -> Result<()>;
+
+    fn load_projects(&mut self) -> Result<Vec<Project>>;
+    fn save_projects(&mut self, projects: &[Project]) -> Result<()>;
+
+    fn load_workspaces(&mut self) -> Result<Vec<Workspace>>;
+    fn save_workspaces(&mut self, workspaces: &[Workspace]) -> Result<()>;
+
+    /// Deleted tasks, keyed by ID like [`load_tasks`](Self::load_tasks).
+    fn load_trash(&mut self) -> Result<HashMap<u32, Task>>;
+    fn save_trash(&mut self, trash: &HashMap<u32, Task>) -> Result<()>;
+
+    /// Archived tasks, kept apart so the live task set stays small.
+    fn load_archive(&mut self) -> Result<HashMap<u32, Task>>;
+    fn save_archive(&mut self, archive: &HashMap<u32, Task>) -> Result<()>;
+
+    /// Starts grouping the saves that follow into one atomic write, for
+    /// backends that can. The default does nothing.
+    fn begin(&mut self) -> Result<()> {
+        Ok(())
+    }
+
+    /// Applies the saves since [`begin`](Self::begin).
+    fn commit(&mut self) -> Result<()> {
+        Ok(())
+    }
+
+    /// Discards the saves since [`begin`](Self::begin), where possible.
+    fn rollback(&mut self) -> Result<()> {
+        Ok(())
+    }
+
+    /// Takes an exclusive lock shared with other processes using the same
+    /// storage, blocking until it is available. Backends that are private to
+    /// the process don't need to override this.
+    fn lock(&mut self) -> Result<StorageLock> {
+        Ok(StorageLock::none())
+    }
+}
+
+/// An exclusive cross-process storage lock, released on drop.
+pub struct StorageLock {
+    _file: Option<File>,
+}
+
+impl StorageLock {
+    /// A lock that guards nothing.
+    pub fn none() -> Self {
+        Self { _file: None }
+    }
+
+    /// Locks `path`, creating it if needed. The file's contents are unused.
+    pub fn acquire(path: &Path) -> Result<Self> {
+        let file = OpenOptions::new().create(true).truncate(false).write(true).open(path)?;
+        file.lock()?;
+        Ok(Self { _file: Some(file) })
+    }
+}
+
+/// Copies every user and task from `from` into `to`, e.g. to move existing
+/// `tasks.json`/`users.json` data into a SQLite database.
+///
+/// Refuses to run if `to` already holds any data. Returns the number of users
+/// and tasks imported.
+pub fn import(from: &mut dyn Storage, to: &mut dyn Storage) -> Result<(usize, usize)> {
+    if !to.load_users()?.is_empty() || !to.load_tasks()?.is_empty() {
+        return Err(TodoError::Validation("Target storage is not empty"));
+    }
+
+    let users = from.load_users()?;
+    let mut tasks = from.load_tasks()?;
+    let mut trash = from.load_trash()?;
+    let mut archive = from.load_archive()?;
+    // The legacy `completed` flag isn't written back, so convert it now.
+    for task in tasks.values_mut().chain(trash.values_mut()).chain(archive.values_mut()) {
+        task.migrate_legacy();
+    }
+    to.save_users(&users)?;
+    to.save_workspaces(&from.load_workspaces()?)?;
+    to.save_projects(&from.load_projects()?)?;
+    if let Some(id) = from.load_next_task_id()? {
+        to.save_next_task_id(id)?;
+    }
+    to.save_trash(&trash)?;
+    to.save_archive(&archive)?;
+    to.save_tasks(&tasks)?;
+    Ok((users.len(), tasks.len()))
+}
+
+/// Opens a backend from a spec string: `json`, `memory`, `sqlite` or
+/// `sqlite:<path>`.
+pub fn open(spec: &str) -> Result<Box<dyn Storage>> {
+    match spec.split_once(':') {
+        None if spec == "json" => Ok(Box::new(JsonStorage::default())),
+        None if spec == "memory" => Ok(Box::new(MemoryStorage::default())),
+        None if spec == "sqlite" => Ok(Box::new(SqliteStorage::open("todo.db")?)),
+        Some(("sqlite", path)) => Ok(Box::new(SqliteStorage::open(path)?)),
+        _ => Err(TodoError::Validation("Unknown storage backend")),
+    }
+}
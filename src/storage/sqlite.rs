@@ -0,0 +1,258 @@
+use std::collections::HashMap;
+use std::ffi::OsString;
+use std::path::{Path, PathBuf};
+
+use rusqlite::{params, Connection, OptionalExtension};
+
+use super::{Storage, StorageLock};
+use crate::error::Result;
+use crate::model::{Project, Task, User, Workspace};
+
+/// Schema migrations, applied in order. The database's `user_version` pragma
+/// records how many have run; append new entries, never edit existing ones.
+const MIGRATIONS: &[&str] = &[
+    "CREATE TABLE IF NOT EXISTS users (
+         username TEXT PRIMARY KEY,
+         data     TEXT NOT NULL
+     );
+     CREATE TABLE IF NOT EXISTS tasks (
+         id      INTEGER PRIMARY KEY,
+         user_id TEXT NOT NULL,
+         data    TEXT NOT NULL
+     );",
+    "CREATE INDEX IF NOT EXISTS idx_tasks_user_id ON tasks (user_id);",
+    "CREATE TABLE meta (
+         key   TEXT PRIMARY KEY,
+         value INTEGER NOT NULL
+     );",
+    "CREATE TABLE projects (
+         user_id TEXT NOT NULL,
+         name    TEXT NOT NULL,
+         data    TEXT NOT NULL,
+         PRIMARY KEY (user_id, name)
+     );",
+    "CREATE TABLE trash (
+         id      INTEGER PRIMARY KEY,
+         user_id TEXT NOT NULL,
+         data    TEXT NOT NULL
+     );",
+    "CREATE TABLE archive (
+         id      INTEGER PRIMARY KEY,
+         user_id TEXT NOT NULL,
+         data    TEXT NOT NULL
+     );
+     CREATE INDEX idx_archive_user_id ON archive (user_id);",
+    "CREATE TABLE workspaces (
+         name TEXT PRIMARY KEY,
+         data TEXT NOT NULL
+     );",
+    "CREATE INDEX IF NOT EXISTS idx_trash_user_id ON trash (user_id);",
+];
+
+/// Stores tasks and users in a SQLite database file.
+///
+/// Each row keeps the serde representation of the record in a `data` column,
+/// so new model fields don't require a schema change. Columns that are
+/// queried on, like `tasks.user_id`, are promoted and indexed.
+pub struct SqliteStorage {
+    conn: Connection,
+    lock_path: Option<PathBuf>,
+}
+
+impl SqliteStorage {
+    /// Opens (or creates) the database at `path`.
+    pub fn open(path: impl AsRef<Path>) -> Result<Self> {
+        let mut lock_path = OsString::from(path.as_ref().as_os_str());
+        lock_path.push(".lock");
+        Self::from_connection(Connection::open(path)?, Some(lock_path.into()))
+    }
+
+    /// Opens a private in-memory database.
+    pub fn open_in_memory() -> Result<Self> {
+        Self::from_connection(Connection::open_in_memory()?, None)
+    }
+
+    fn from_connection(mut conn: Connection, lock_path: Option<PathBuf>) -> Result<Self> {
+        migrate(&mut conn)?;
+        Ok(Self { conn, lock_path })
+    }
+
+    /// Returns the schema version the database is at.
+    pub fn schema_version(&self) -> Result<usize> {
+        Ok(self.conn.pragma_query_value(None, "user_version", |row| row.get(0))?)
+    }
+}
+
+fn migrate(conn: &mut Connection) -> Result<()> {
+    let version: usize = conn.pragma_query_value(None, "user_version", |row| row.get(0))?;
+
+    for (i, sql) in MIGRATIONS.iter().enumerate().skip(version) {
+        let tx = conn.transaction()?;
+        tx.execute_batch(sql)?;
+        tx.pragma_update(None, "user_version", i + 1)?;
+        tx.commit()?;
+    }
+    Ok(())
+}
+
+/// Reads a table with the `tasks` layout. `table` is always a constant.
+fn load_task_table(conn: &Connection, table: &str) -> Result<HashMap<u32, Task>> {
+    let mut stmt = conn.prepare(&format!("SELECT data FROM {}", table))?;
+    let rows = stmt.query_map([], |row| row.get::<_, String>(0))?;
+
+    let mut tasks = HashMap::new();
+    for data in rows {
+        let task: Task = serde_json::from_str(&data?)?;
+        tasks.insert(task.id, task);
+    }
+    Ok(tasks)
+}
+
+/// Replaces the contents of a table with the `tasks` layout.
+fn save_task_table(conn: &mut Connection, table: &str, tasks: &HashMap<u32, Task>) -> Result<()> {
+    let tx = conn.savepoint()?;
+    tx.execute(&format!("DELETE FROM {}", table), [])?;
+    {
+        let mut stmt = tx.prepare(&format!("INSERT INTO {} (id, user_id, data) VALUES (?1, ?2, ?3)", table))?;
+        for task in tasks.values() {
+            stmt.execute(params![task.id, task.user_id, serde_json::to_string(task)?])?;
+        }
+    }
+    tx.commit()?;
+    Ok(())
+}
+
+impl Storage for SqliteStorage {
+    fn load_tasks(&mut self) -> Result<HashMap<u32, Task>> {
+        load_task_table(&self.conn, "tasks")
+    }
+
+    fn save_tasks(&mut self, tasks: &HashMap<u32, Task>) -> Result<()> {
+        save_task_table(&mut self.conn, "tasks", tasks)
+    }
+
+    fn load_users(&mut self) -> Result<HashMap<String, User>> {
+        let mut stmt = self.conn.prepare("SELECT data FROM users")?;
+        let rows = stmt.query_map([], |row| row.get::<_, String>(0))?;
+
+        let mut users = HashMap::new();
+        for data in rows {
+            let user: User = serde_json::from_str(&data?)?;
+            users.insert(user.username.clone(), user);
+        }
+        Ok(users)
+    }
+
+    fn save_users(&mut self, users: &HashMap<String, User>) -> Result<()> {
+        let tx = self.conn.savepoint()?;
+        tx.execute("DELETE FROM users", [])?;
+        {
+            let mut stmt = tx.prepare("INSERT INTO users (username, data) VALUES (?1, ?2)")?;
+            for user in users.values() {
+                stmt.execute(params![user.username, serde_json::to_string(user)?])?;
+            }
+        }
+        tx.commit()?;
+        Ok(())
+    }
+
+    fn load_next_task_id(&mut self) -> Result<Option<u32>> {
+        Ok(self.conn
+            .query_row("SELECT value FROM meta WHERE key = 'next_task_id'", [], |row| row.get(0))
+            .optional()?)
+    }
+
+    fn save_next_task_id(&mut self, id: u32) -> Result<()> {
+        self.conn.execute(
+            "INSERT INTO meta (key, value) VALUES ('next_task_id', ?1)
+             ON CONFLICT (key) DO UPDATE SET value = excluded.value",
+            params![id],
+        )?;
+        Ok(())
+    }
+
+    fn load_projects(&mut self) -> Result<Vec<Project>> {
+        let mut stmt = self.conn.prepare("SELECT data FROM projects")?;
+        let rows = stmt.query_map([], |row| row.get::<_, String>(0))?;
+
+        let mut projects = Vec::new();
+        for data in rows {
+            projects.push(serde_json::from_str(&data?)?);
+        }
+        Ok(projects)
+    }
+
+    fn save_projects(&mut self, projects: &[Project]) -> Result<()> {
+        let tx = self.conn.savepoint()?;
+        tx.execute("DELETE FROM projects", [])?;
+        {
+            let mut stmt = tx.prepare("INSERT INTO projects (user_id, name, data) VALUES (?1, ?2, ?3)")?;
+            for project in projects {
+                stmt.execute(params![project.user_id, project.name, serde_json::to_string(project)?])?;
+            }
+        }
+        tx.commit()?;
+        Ok(())
+    }
+
+    fn load_workspaces(&mut self) -> Result<Vec<Workspace>> {
+        let mut stmt = self.conn.prepare("SELECT data FROM workspaces")?;
+        let rows = stmt.query_map([], |row| row.get::<_, String>(0))?;
+
+        let mut workspaces = Vec::new();
+        for data in rows {
+            workspaces.push(serde_json::from_str(&data?)?);
+        }
+        Ok(workspaces)
+    }
+
+    fn save_workspaces(&mut self, workspaces: &[Workspace]) -> Result<()> {
+        let tx = self.conn.savepoint()?;
+        tx.execute("DELETE FROM workspaces", [])?;
+        {
+            let mut stmt = tx.prepare("INSERT INTO workspaces (name, data) VALUES (?1, ?2)")?;
+            for workspace in workspaces {
+                stmt.execute(params![workspace.name, serde_json::to_string(workspace)?])?;
+            }
+        }
+        tx.commit()?;
+        Ok(())
+    }
+
+    fn load_trash(&mut self) -> Result<HashMap<u32, Task>> {
+        load_task_table(&self.conn, "trash")
+    }
+
+    fn save_trash(&mut self, trash: &HashMap<u32, Task>) -> Result<()> {
+        save_task_table(&mut self.conn, "trash", trash)
+    }
+
+    fn load_archive(&mut self) -> Result<HashMap<u32, Task>> {
+        load_task_table(&self.conn, "archive")
+    }
+
+    fn save_archive(&mut self, archive: &HashMap<u32, Task>) -> Result<()> {
+        save_task_table(&mut self.conn, "archive", archive)
+    }
+
+    // The saves use savepoints, so they nest inside this transaction and
+    // still stand alone outside one.
+    fn begin(&mut self) -> Result<()> {
+        Ok(self.conn.execute_batch("BEGIN IMMEDIATE")?)
+    }
+
+    fn commit(&mut self) -> Result<()> {
+        Ok(self.conn.execute_batch("COMMIT")?)
+    }
+
+    fn rollback(&mut self) -> Result<()> {
+        Ok(self.conn.execute_batch("ROLLBACK")?)
+    }
+
+    fn lock(&mut self) -> Result<StorageLock> {
+        match &self.lock_path {
+            Some(path) => StorageLock::acquire(path),
+            None => Ok(StorageLock::none()),
+        }
+    }
+}
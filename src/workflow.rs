@@ -0,0 +1,55 @@
+use std::collections::HashSet;
+
+use crate::model::Status;
+
+/// Which status changes [`TodoApp::set_status`](crate::TodoApp::set_status)
+/// accepts.
+#[derive(Debug, Clone)]
+pub struct Workflow {
+    allowed: HashSet<(Status, Status)>,
+}
+
+impl Workflow {
+    /// A workflow that allows no transitions; build one up with [`allow`](Self::allow).
+    pub fn empty() -> Self {
+        Self { allowed: HashSet::new() }
+    }
+
+    pub fn allow(mut self, from: Status, to: Status) -> Self {
+        self.allowed.insert((from, to));
+        self
+    }
+
+    pub fn forbid(mut self, from: Status, to: Status) -> Self {
+        self.allowed.remove(&(from, to));
+        self
+    }
+
+    pub fn allows(&self, from: Status, to: Status) -> bool {
+        self.allowed.contains(&(from, to))
+    }
+}
+
+impl Default for Workflow {
+    /// Open tasks can move freely between the open states or be closed; closed
+    /// tasks can only be reopened to `todo`. Blocked tasks must be unblocked
+    /// before they can be done.
+    fn default() -> Self {
+        use Status::*;
+
+        Self::empty()
+            .allow(Todo, InProgress)
+            .allow(Todo, Blocked)
+            .allow(Todo, Done)
+            .allow(Todo, Cancelled)
+            .allow(InProgress, Todo)
+            .allow(InProgress, Blocked)
+            .allow(InProgress, Done)
+            .allow(InProgress, Cancelled)
+            .allow(Blocked, Todo)
+            .allow(Blocked, InProgress)
+            .allow(Blocked, Cancelled)
+            .allow(Done, Todo)
+            .allow(Cancelled, Todo)
+    }
+}